@@ -39,5 +39,78 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     day_tests!(25, c, day25);
 }
 
-criterion_group!(benches, criterion_benchmark);
+pub fn day22_backends(c: &mut Criterion) {
+    let data = read_data("day22/input").unwrap();
+    let mut group = c.benchmark_group("day22-backends-1");
+    for backend in day22::Backend::ALL {
+        group.bench_function(backend.name(),
+            |b| b.iter(|| day22::solve_part_one_with(&data, backend)));
+    }
+    group.finish();
+    let mut group = c.benchmark_group("day22-backends-2");
+    for backend in day22::Backend::ALL {
+        group.bench_function(backend.name(),
+            |b| b.iter(|| day22::solve_part_two_with(&data, backend)));
+    }
+    group.finish();
+}
+
+pub fn day12_counting(c: &mut Criterion) {
+    let data = read_data("day12/input").unwrap();
+    let caves = data.parse::<day12::CaveSystem>().unwrap();
+    let mut group = c.benchmark_group("day12-counting");
+    group.bench_function("bfs-2", |b| b.iter(|| day12::bfs::bfs_count(&data, true)));
+    group.bench_function("memo-2", |b| b.iter(||
+        caves.count_paths_memo(day12::RevisitPolicy::ONE_TWICE)));
+    group.bench_function("bitmask-2", |b| b.iter(|| caves.count_paths_bitmask(true)));
+    group.finish();
+}
+
+pub fn day15_search(c: &mut Criterion) {
+    let data = read_data("day15/input").unwrap();
+    let cave = data.parse::<day15::Cave>().unwrap().tiled(5).unwrap();
+    let mut group = c.benchmark_group("day15-search");
+    group.bench_function("graph", |b| b.iter(|| day15::graph_lowest_risk_tiled(&data)));
+    for (name, search) in [("dijkstra", day15::Search::Dijkstra), ("astar", day15::Search::AStar)] {
+        group.bench_function(name, |b| b.iter(||
+            cave.shortest_path((0, 0), cave.end(), search)));
+    }
+    group.finish();
+}
+
+pub fn day20_enhancers(c: &mut Criterion) {
+    let data = read_data("day20/input").unwrap();
+    let mut group = c.benchmark_group("day20-enhancers");
+    group.bench_function("sparse-50", |b| b.iter(|| {
+        let mut machine = day20::Enhancer::unparse(&data);
+        for _ in 0..50 { machine.step(); }
+        machine.count()
+    }));
+    group.bench_function("dense-50", |b| b.iter(||
+        data.parse::<day20::DenseEnhancer>().unwrap().run(50)));
+    group.bench_function("life-50", |b| b.iter(|| {
+        let image = data.parse::<day20::DenseEnhancer>().unwrap().image().clone();
+        day20::Automaton::new(image, day20::Neighbourhood::moore(1), day20::Rule::life())
+            .unwrap().run(50)
+    }));
+    group.sample_size(10);
+    group.bench_function("dense-1000", |b| b.iter(||
+        data.parse::<day20::DenseEnhancer>().unwrap().run(1000)));
+    group.finish();
+}
+
+pub fn day21_quantum(c: &mut Criterion) {
+    let data = read_data("day21/input").unwrap();
+    let starts = day21::parse_starts(&data).unwrap();
+    let mut group = c.benchmark_group("day21-quantum");
+    for win_score in [21, 40, 50] {
+        let config = day21::GameConfig::DIRAC.with_win_score(win_score);
+        group.bench_function(&format!("win-{}", win_score),
+            |b| b.iter(|| day21::quantum_wins(&config, &starts)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, day12_counting, day15_search,
+    day20_enhancers, day21_quantum, day22_backends);
 criterion_main!(benches);
@@ -0,0 +1,131 @@
+//! Module counting paths with a breadth-first search over a [`petgraph`]
+//! graph
+//!
+//! Every partial path is kept in the queue, so counting takes as long as
+//! listing every path. The tests count paths with it to cross-check the
+//! [`CaveSystem`] iterator, and `bfs-2` is the slowest line of the
+//! `day12-counting` benchmark.
+//!
+//! [`CaveSystem`]: crate::caves::CaveSystem
+
+use petgraph::prelude::*;
+use std::collections::{HashMap, VecDeque, HashSet};
+
+/// Undirected graph of the caves, with their names
+pub type CaveGraph<'a> = Graph<&'a str, (), Undirected>;
+
+/// Build the graph of the caves, with the node of every cave name
+///
+/// # Panics
+///
+/// If a line is not a pair of caves, panics.
+#[must_use]
+pub fn build_graph(data: &str) -> (CaveGraph<'_>, HashMap<&str, NodeIndex>) {
+    let mut graph = Graph::<&str, (), Undirected>::new_undirected();
+    let edges = data.trim().split('\n')
+        .map(|line| {
+            let sl = line.split('-').collect::<Vec<&str>>();
+            (*sl.first().unwrap(), *sl.get(1).unwrap())
+        })
+        .collect::<Vec<(&str, &str)>>();
+    let mut vertice_names = edges.iter()
+        .flat_map(|x| vec![x.0, x.1])
+        .collect::<Vec<&str>>();
+    // Only adjacent duplicates are removed, so sort first
+    vertice_names.sort_unstable();
+    vertice_names.dedup();
+   let vertices = vertice_names.iter()
+        .map(|&v| (v, graph.add_node(v)))
+        .collect::<HashMap<&str, NodeIndex>>();
+   edges.iter()
+        .map(|&(s, e)| (vertices.get(s).unwrap(), vertices.get(e).unwrap()))
+        .for_each(|s| {graph.add_edge(*s.0, *s.1, ());});
+    (graph, vertices)
+}
+
+/// Count the paths with a breadth-first search
+///
+/// # Arguments
+///
+///  - `data` : the connections between caves.
+///  - `twice` : whether a single small cave can be visited twice.
+///
+/// # Panics
+///
+/// If the input is incorrect, or misses `start` or `end`, panics.
+#[must_use]
+pub fn bfs_count(data: &str, twice: bool) -> usize {
+    let (graph, vertices) = build_graph(data);
+    let small_vertices = vertices.values()
+        .filter(|&&x| graph[x].chars().any(char::is_lowercase))
+        .collect::<HashSet<&NodeIndex>>();
+    let (start, end) = (*vertices.get("start").unwrap(), *vertices.get("end").unwrap());
+    if twice {
+        even_more_messed_up_dfs(&graph, start, end, &small_vertices)
+    } else {
+        messed_up_dfs(&graph, start, end, &small_vertices)
+    }
+}
+
+fn messed_up_dfs(graph: &Graph<&str, (), Undirected, u32>,
+                 a: NodeIndex, b: NodeIndex, small: &HashSet<&NodeIndex>) -> usize {
+    let mut paths: VecDeque<Vec<NodeIndex>> = VecDeque::new();
+    let mut finished: usize = 0;
+    paths.push_front(vec![a]);
+    while !paths.is_empty() {
+        // Pop a path
+        let current_study = paths.pop_front().unwrap();
+        let back = *current_study.last().unwrap();
+        // If it's the end, stop
+        if back == b {
+            finished += 1;
+            continue;
+        }
+        // Find its neighbors
+        for neigh in graph.neighbors(back) {
+            if small.contains(&neigh) && current_study.contains(&neigh) {
+                continue;
+            }
+            // Push new paths to the back
+            let mut v_new: Vec<NodeIndex> = current_study.clone();
+            v_new.push(neigh);
+            paths.push_back(v_new);
+        }
+    }
+    finished
+}
+
+fn even_more_messed_up_dfs(graph: &Graph<&str, (), Undirected, u32>,
+                           a: NodeIndex, b: NodeIndex,
+                           small: &HashSet<&NodeIndex>) -> usize {
+    let mut finished: usize = 0;
+    let mut paths: VecDeque<(bool, Vec<NodeIndex>)> = VecDeque::new();
+    paths.push_front((false, vec![a]));
+    while !paths.is_empty() {
+        // Pop a path
+        let (small_visited_twice, current_study) = paths.pop_front().unwrap();
+        let back = *current_study.last().unwrap();
+        // If it's the end, stop
+        if back == b {
+            finished += 1;
+            continue;
+        }
+        // Find its neighbors
+        for neigh in graph.neighbors(back) {
+            let mut duped: bool = false;
+            if small.contains(&neigh) && current_study.contains(&neigh) {
+                if a == neigh || small_visited_twice {
+                    continue;
+                } else if current_study.iter()
+                    .filter(|&x| *x == neigh).count() == 1 {
+                    duped = true;
+                }
+            }
+            // Push new paths to the back
+            let mut v_new: Vec<NodeIndex> = current_study.clone();
+            v_new.push(neigh);
+            paths.push_back((small_visited_twice || duped, v_new));
+        }
+    }
+    finished
+}
@@ -0,0 +1,338 @@
+//! Module defining the [`CaveSystem`] and searching it for paths
+
+use std::collections::HashMap;
+
+use crate::ParseCaveError;
+
+/// Count of a state that was not computed yet
+const UNKNOWN: u128 = u128::MAX;
+
+/// How many times small caves can be visited
+///
+/// Every small cave can be visited once, and up to `extra_caves` of them
+/// can be visited again, up to `max_visits` times. The `start` cave is
+/// never visited again, and paths stop as soon as they reach `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RevisitPolicy {
+    /// Number of small caves that can be visited more than once
+    pub extra_caves: usize,
+    /// Number of times those small caves can be visited
+    pub max_visits: usize
+}
+
+impl RevisitPolicy {
+    /// Small caves are visited at most once, as in part one
+    pub const ONCE: RevisitPolicy = RevisitPolicy { extra_caves: 0, max_visits: 1 };
+    /// A single small cave can be visited twice, as in part two
+    pub const ONE_TWICE: RevisitPolicy = RevisitPolicy { extra_caves: 1, max_visits: 2 };
+}
+
+/// Caves and the passages between them
+///
+/// Caves are numbered in the order they first appear, and small caves are
+/// the ones with a lowercase name.
+#[derive(Debug, Clone)]
+pub struct CaveSystem {
+    names: Vec<String>,
+    small: Vec<bool>,
+    neighbours: Vec<Vec<usize>>,
+    start: usize,
+    end: usize
+}
+
+impl CaveSystem {
+    /// Names of the caves
+    #[must_use]
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Export the caves to Graphviz DOT
+    ///
+    /// Big caves are drawn as filled boxes, small caves as ellipses, and
+    /// `start` and `end` as double circles.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph caves {\n");
+        for (cave, name) in self.names.iter().enumerate() {
+            let style = if cave == self.start || cave == self.end {
+                "shape=doublecircle"
+            } else if self.small[cave] {
+                "shape=ellipse"
+            } else {
+                "shape=box, style=filled, fillcolor=lightblue"
+            };
+            dot.push_str(&format!("    \"{}\" [{}];\n", name, style));
+        }
+        for (cave, neighbours) in self.neighbours.iter().enumerate() {
+            for &other in neighbours.iter().filter(|&&other| other >= cave) {
+                dot.push_str(&format!("    \"{}\" -- \"{}\";\n",
+                    self.names[cave], self.names[other]));
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Count the paths from `start` to `end`
+    ///
+    /// Policies where at most one small cave is visited twice are counted
+    /// with [`CaveSystem::count_paths_bitmask`], all others with
+    /// [`CaveSystem::count_paths_memo`].
+    #[must_use]
+    pub fn count_paths(&self, policy: RevisitPolicy) -> u128 {
+        if policy.max_visits <= 2 && policy.extra_caves <= 1 {
+            self.count_paths_bitmask(policy.extra_caves == 1 && policy.max_visits == 2)
+        } else {
+            self.count_paths_memo(policy)
+        }
+    }
+
+    /// Count the paths from `start` to `end`, with any policy
+    ///
+    /// The number of paths from a cave only depends on how many times every
+    /// small cave has been visited, so it is remembered for every such
+    /// state.
+    #[must_use]
+    pub fn count_paths_memo(&self, policy: RevisitPolicy) -> u128 {
+        let mut visits = Visits::new(self, policy);
+        let mut memo = HashMap::new();
+        self.count_from(self.start, &mut visits, &mut memo)
+    }
+
+    fn count_from(&self, cave: usize, visits: &mut Visits,
+        memo: &mut HashMap<(usize, Vec<u8>), u128>) -> u128 {
+        if cave == self.end { return 1; }
+        if let Some(&count) = memo.get(&(cave, visits.counts.clone())) {
+            return count;
+        }
+        let mut count = 0;
+        for &next in &self.neighbours[cave] {
+            if visits.enter(self, next) {
+                count += self.count_from(next, visits, memo);
+                visits.leave(self, next);
+            }
+        }
+        memo.insert((cave, visits.counts.clone()), count);
+        count
+    }
+
+    /// Count the paths from `start` to `end`, where small caves are visited
+    /// once, except for a single one visited twice if `twice` is set
+    ///
+    /// The state of a path is the cave it is in, the set of small caves it
+    /// went through as a bitmask, and whether a small cave was already
+    /// visited twice. The count of every state is remembered in a dense
+    /// table, which doubles in size with every small cave, as long as it
+    /// stays small enough. Larger systems remember only the states they
+    /// reach, and those with more than 64 small caves are counted with
+    /// [`CaveSystem::count_paths_memo`].
+    #[must_use]
+    pub fn count_paths_bitmask(&self, twice: bool) -> u128 {
+        let mut bits = vec![0_u64; self.names.len()];
+        let mut next_bit = 0;
+        for (cave, &small) in self.small.iter().enumerate() {
+            // Paths never come back to `start`, and stop at `end`
+            if !small || cave == self.start || cave == self.end { continue; }
+            if next_bit == u64::BITS {
+                let policy = if twice { RevisitPolicy::ONE_TWICE } else { RevisitPolicy::ONCE };
+                return self.count_paths_memo(policy);
+            }
+            bits[cave] = 1 << next_bit;
+            next_bit += 1;
+        }
+        let states = 1_usize.checked_shl(next_bit)
+            .and_then(|masks| masks.checked_mul(self.names.len() * 2))
+            .filter(|&states| states <= MAX_DENSE_STATES);
+        if let Some(states) = states {
+            let mut memo = DenseMemo { caves: self.names.len(), counts: vec![UNKNOWN; states] };
+            self.count_masked(self.start, 0, !twice, &bits, &mut memo)
+        } else {
+            self.count_masked(self.start, 0, !twice, &bits, &mut HashMap::new())
+        }
+    }
+
+    fn count_masked<M: MaskMemo>(&self, cave: usize, visited: u64, twice_used: bool,
+        bits: &[u64], memo: &mut M) -> u128 {
+        if cave == self.end { return 1; }
+        let state = (cave, visited, twice_used);
+        if let Some(count) = memo.get(state) {
+            return count;
+        }
+        let mut count = 0;
+        for &next in &self.neighbours[cave] {
+            if next == self.start { continue; }
+            if visited & bits[next] == 0 {
+                count += self.count_masked(next, visited | bits[next], twice_used, bits, memo);
+            } else if !twice_used {
+                count += self.count_masked(next, visited, true, bits, memo);
+            }
+        }
+        memo.set(state, count);
+        count
+    }
+
+    /// Iterate over the paths from `start` to `end`, as comma-separated
+    /// names such as `start,A,b,end`
+    #[must_use]
+    pub fn paths(&self, policy: RevisitPolicy) -> Paths<'_> {
+        Paths { caves: self, visits: Visits::new(self, policy), stack: vec![(self.start, 0)] }
+    }
+}
+
+/// Largest dense table of counts, in states, kept for
+/// [`CaveSystem::count_paths_bitmask`]
+const MAX_DENSE_STATES: usize = 1 << 21;
+
+/// State of a path while counting with a bitmask: its cave, the small caves
+/// it went through, and whether a small cave was visited twice
+type MaskState = (usize, u64, bool);
+
+/// Counts remembered by [`CaveSystem::count_paths_bitmask`]
+trait MaskMemo {
+    fn get(&self, state: MaskState) -> Option<u128>;
+    fn set(&mut self, state: MaskState, count: u128);
+}
+
+/// Every state, in a table indexed by its fields
+struct DenseMemo {
+    caves: usize,
+    counts: Vec<u128>
+}
+
+impl DenseMemo {
+    fn index(&self, (cave, visited, twice_used): MaskState) -> usize {
+        (visited as usize * self.caves + cave) * 2 + usize::from(twice_used)
+    }
+}
+
+impl MaskMemo for DenseMemo {
+    fn get(&self, state: MaskState) -> Option<u128> {
+        Some(self.counts[self.index(state)]).filter(|&c| c != UNKNOWN)
+    }
+
+    fn set(&mut self, state: MaskState, count: u128) {
+        let index = self.index(state);
+        self.counts[index] = count;
+    }
+}
+
+/// Only the states reached
+impl MaskMemo for HashMap<MaskState, u128> {
+    fn get(&self, state: MaskState) -> Option<u128> {
+        HashMap::get(self, &state).copied()
+    }
+
+    fn set(&mut self, state: MaskState, count: u128) {
+        self.insert(state, count);
+    }
+}
+
+/// Number of visits of every small cave on the current path
+#[derive(Debug, Clone)]
+struct Visits {
+    policy: RevisitPolicy,
+    counts: Vec<u8>,
+    /// Number of small caves visited more than once
+    extra: usize
+}
+
+impl Visits {
+    fn new(caves: &CaveSystem, policy: RevisitPolicy) -> Visits {
+        let mut counts = vec![0; caves.names.len()];
+        counts[caves.start] = 1;
+        Visits { policy, counts, extra: 0 }
+    }
+
+    /// Try to move into a cave
+    fn enter(&mut self, caves: &CaveSystem, cave: usize) -> bool {
+        if cave == caves.start { return false; }
+        if !caves.small[cave] { return true; }
+        let count = self.counts[cave] as usize;
+        if count >= self.policy.max_visits.max(1) { return false; }
+        if count == 1 {
+            if self.extra >= self.policy.extra_caves { return false; }
+            self.extra += 1;
+        }
+        self.counts[cave] += 1;
+        true
+    }
+
+    /// Move back out of a cave entered last
+    fn leave(&mut self, caves: &CaveSystem, cave: usize) {
+        if !caves.small[cave] { return; }
+        self.counts[cave] -= 1;
+        if self.counts[cave] == 1 { self.extra -= 1; }
+    }
+}
+
+/// Lazy iterator over the paths of a [`CaveSystem`], depth first
+pub struct Paths<'c> {
+    caves: &'c CaveSystem,
+    visits: Visits,
+    /// Caves of the current path, with the next neighbour to try
+    stack: Vec<(usize, usize)>
+}
+
+impl Iterator for Paths<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some(&(cave, next)) = self.stack.last() {
+            let Some(&neighbour) = self.caves.neighbours[cave].get(next) else {
+                self.stack.pop();
+                self.visits.leave(self.caves, cave);
+                continue;
+            };
+            self.stack.last_mut().unwrap().1 += 1;
+            if neighbour == self.caves.end {
+                let mut path = self.stack.iter()
+                    .map(|&(c, _)| self.caves.names[c].as_str())
+                    .collect::<Vec<&str>>();
+                path.push(&self.caves.names[neighbour]);
+                return Some(path.join(","));
+            }
+            if self.visits.enter(self.caves, neighbour) {
+                self.stack.push((neighbour, 0));
+            }
+        }
+        None
+    }
+}
+
+/// Read the passages between caves, one `a-b` pair per line
+impl std::str::FromStr for CaveSystem {
+    type Err = ParseCaveError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut names = Vec::new();
+        let mut neighbours: Vec<Vec<usize>> = Vec::new();
+        for line in data.trim().lines() {
+            let (a, b) = line.split_once('-')
+                .filter(|(a, b)| !a.is_empty() && !b.is_empty() && !b.contains('-'))
+                .ok_or_else(|| ParseCaveError::BadPassage(line.to_string()))?;
+            let [a, b] = [a, b].map(|name| *ids.entry(name).or_insert_with(|| {
+                names.push(name.to_string());
+                neighbours.push(Vec::new());
+                names.len() - 1
+            }));
+            if !neighbours[a].contains(&b) {
+                neighbours[a].push(b);
+                if a != b { neighbours[b].push(a); }
+            }
+        }
+        let small = names.iter()
+            .map(|n| n.chars().any(char::is_lowercase))
+            .collect::<Vec<bool>>();
+        // Going back and forth between big caves never ends
+        for (cave, next) in neighbours.iter().enumerate() {
+            if let Some(&other) = next.iter().find(|&&other| !small[cave] && !small[other]) {
+                return Err(ParseCaveError::AdjacentBigCaves(names[cave].clone(),
+                    names[other].clone()));
+            }
+        }
+        let start = *ids.get("start").ok_or(ParseCaveError::MissingStart)?;
+        let end = *ids.get("end").ok_or(ParseCaveError::MissingEnd)?;
+        Ok(CaveSystem { names, small, neighbours, start, end })
+    }
+}
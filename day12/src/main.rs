@@ -2,12 +2,27 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day12;
+use day12::{solve_part_one, solve_part_two, CaveSystem, RevisitPolicy};
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--dot` exports the caves to Graphviz
+    if std::env::args().any(|a| a == "--dot") {
+        println!("{}", data.parse::<CaveSystem>()?.to_dot());
+        return Ok(());
+    }
+    // `--paths <k>,<m>` lists every path where `k` small caves can be
+    // visited up to `m` times
+    if let Some(policy) = std::env::args().skip_while(|a| a != "--paths").nth(1) {
+        let (k, m) = policy.split_once(',').ok_or("expected --paths <k>,<m>")?;
+        let policy = RevisitPolicy { extra_caves: k.parse::<usize>()?, max_visits: m.parse::<usize>()? };
+        for path in data.parse::<CaveSystem>()?.paths(policy) {
+            println!("{}", path);
+        }
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -22,41 +37,129 @@ mod test {
     fn day12_01_example1() {
         let data = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
         let expected = 10;
-        assert_eq!(expected, solve_part_one(&data));
+        assert_eq!(expected, solve_part_one(data));
     }
 
     #[test]
     fn day12_01_example2() {
         let data = "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc\n";
         let expected = 19;
-        assert_eq!(expected, solve_part_one(&data));
+        assert_eq!(expected, solve_part_one(data));
     }
 
     #[test]
     fn day12_01_example3() {
         let data = "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW";
         let expected = 226;
-        assert_eq!(expected, solve_part_one(&data));
+        assert_eq!(expected, solve_part_one(data));
     }
 
     #[test]
     fn day12_02_example1() {
         let data = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
         let expected = 36;
-        assert_eq!(expected, solve_part_two(&data));
+        assert_eq!(expected, solve_part_two(data));
     }
 
     #[test]
     fn day12_02_example2() {
         let data = "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc\n";
         let expected = 103;
-        assert_eq!(expected, solve_part_two(&data));
+        assert_eq!(expected, solve_part_two(data));
     }
 
     #[test]
     fn day12_02_example3() {
         let data = "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW";
         let expected = 3509;
-        assert_eq!(expected, solve_part_two(&data));
+        assert_eq!(expected, solve_part_two(data));
+    }
+
+    #[test]
+    fn day12_revisit_policies() {
+        let data = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+        let caves = data.parse::<CaveSystem>().unwrap();
+        let mut paths = caves.paths(RevisitPolicy::ONCE).collect::<Vec<String>>();
+        paths.sort();
+        assert_eq!(vec!["start,A,b,A,c,A,end", "start,A,b,A,end", "start,A,b,end",
+            "start,A,c,A,b,A,end", "start,A,c,A,b,end", "start,A,c,A,end", "start,A,end",
+            "start,b,A,c,A,end", "start,b,A,end", "start,b,end"], paths);
+        assert!(caves.paths(RevisitPolicy::ONE_TWICE).any(|p| p == "start,A,b,A,b,A,c,A,end"));
+        for data in [data,
+            "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc",
+            "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW"] {
+            let caves = data.parse::<CaveSystem>().unwrap();
+            for (policy, twice) in [(RevisitPolicy::ONCE, false), (RevisitPolicy::ONE_TWICE, true)] {
+                let paths = caves.paths(policy).collect::<std::collections::HashSet<String>>();
+                assert_eq!(day12::bfs::bfs_count(data, twice), paths.len());
+                assert_eq!(paths.len() as u128, caves.count_paths(policy));
+            }
+        }
+        // Two small caves visited up to three times
+        let policy = RevisitPolicy { extra_caves: 2, max_visits: 3 };
+        let count = caves.count_paths(policy);
+        assert_eq!(count, caves.paths(policy).count() as u128);
+        assert!(count > caves.count_paths(RevisitPolicy::ONE_TWICE));
+        assert!(caves.paths(policy).any(|p| p == "start,A,b,A,b,A,c,A,c,A,b,end"));
+    }
+
+    #[test]
+    fn day12_bitmask_counts() {
+        let data = "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW";
+        let caves = data.parse::<CaveSystem>().unwrap();
+        assert_eq!((226, 3509), (caves.count_paths_bitmask(false), caves.count_paths_bitmask(true)));
+        assert_eq!(3509, caves.count_paths_memo(RevisitPolicy::ONE_TWICE));
+        // Every small cave next to every other one, and to a big cave
+        let dense = |n: usize| {
+            let small = (0..n).map(|i| format!("c{}", i)).collect::<Vec<String>>();
+            let mut lines = vec!["start-BIG".to_string(), "BIG-end".to_string()];
+            for (i, a) in small.iter().enumerate() {
+                lines.push(format!("{}-BIG", a));
+                lines.extend(small[i + 1..].iter().map(|b| format!("{}-{}", a, b)));
+            }
+            lines.join("\n")
+        };
+        let data = dense(8);
+        let caves = data.parse::<CaveSystem>().unwrap();
+        assert_eq!(caves.count_paths_memo(RevisitPolicy::ONE_TWICE), caves.count_paths_bitmask(true));
+        let data = dense(16);
+        let caves = data.parse::<CaveSystem>().unwrap();
+        assert_eq!(273_264_523_039_710_346_113, caves.count_paths_bitmask(true));
+        // Too many small caves for a dense table, or even for a bitmask
+        let chain = |n: usize| (0..n)
+            .flat_map(|i| [format!("c{}-B{}", i, i), format!("B{}-c{}", i, i + 1),
+                format!("c{}-X{}", i, i)])
+            .chain([String::from("start-c0"), format!("c{}-end", n)])
+            .collect::<Vec<String>>()
+            .join("\n");
+        for n in [30, 62, 63, 64, 70] {
+            let caves = chain(n).parse::<CaveSystem>().unwrap();
+            assert_eq!(caves.count_paths_memo(RevisitPolicy::ONCE), caves.count_paths(RevisitPolicy::ONCE));
+            assert_eq!(caves.count_paths_memo(RevisitPolicy::ONE_TWICE),
+                caves.count_paths(RevisitPolicy::ONE_TWICE));
+        }
+    }
+
+    #[test]
+    fn day12_parser() {
+        use day12::ParseCaveError;
+        let data = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+        let caves = data.parse::<CaveSystem>().unwrap();
+        assert_eq!(vec!["start", "A", "b", "c", "d", "end"], caves.names());
+        let (graph, _) = day12::bfs::build_graph(data);
+        assert_eq!(6, graph.node_count());
+        // Passages given twice are only followed once
+        let twice = "start-A\nA-end\nend-A\nstart-A".parse::<CaveSystem>().unwrap();
+        assert_eq!(1, twice.count_paths(RevisitPolicy::ONCE));
+        assert_eq!(Err(ParseCaveError::AdjacentBigCaves("A".into(), "B".into())),
+            "start-A\nA-B\nB-end".parse::<CaveSystem>().map(|_| ()));
+        assert_eq!(Err(ParseCaveError::MissingStart), "a-end".parse::<CaveSystem>().map(|_| ()));
+        assert_eq!(Err(ParseCaveError::MissingEnd), "start-a".parse::<CaveSystem>().map(|_| ()));
+        assert_eq!(Err(ParseCaveError::BadPassage("start-a-end".into())),
+            "start-a-end".parse::<CaveSystem>().map(|_| ()));
+        assert_eq!(Err(ParseCaveError::BadPassage("start".into())),
+            "start-a\nstart\na-end".parse::<CaveSystem>().map(|_| ()));
+        assert_eq!("graph caves {\n    \"start\" [shape=doublecircle];\n    \"A\" [shape=box, style=filled, fillcolor=lightblue];\n    \"b\" [shape=ellipse];\n    \"end\" [shape=doublecircle];\n    \"start\" -- \"A\";\n    \"start\" -- \"b\";\n    \"A\" -- \"b\";\n    \"A\" -- \"end\";\n    \"b\" -- \"end\";\n}",
+            "start-A\nstart-b\nA-b\nA-end\nb-end".parse::<CaveSystem>().unwrap().to_dot());
     }
 }
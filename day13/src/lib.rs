@@ -1,26 +1,43 @@
 //! Library module with all the logic
 
-use std::collections::HashSet;
+pub mod paper;
+pub use paper::{FoldInstruction, Manual, Paper};
 
-enum FoldInstruction {
-    FoldX(usize),
-    FoldY(usize)
+/// Error thrown when reading an incorrect manual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseManualError {
+    /// A dot is not an `x,y` pair
+    BadDot(String),
+    /// A fold is neither `fold along x=N` nor `fold along y=N`
+    BadFold(String),
+    /// There is no blank line between the dots and the folds
+    MissingFolds
 }
-
-fn fold_from_line(line: &str) -> FoldInstruction {
-    match &line.split('=').collect::<Vec<&str>>()[..2] {
-        ["fold along x", x] => FoldInstruction::FoldX(x.parse::<usize>().unwrap()),
-        ["fold along y", y] => FoldInstruction::FoldY(y.parse::<usize>().unwrap()),
-        _ => panic!("Invalid line '{}'", line)
+impl std::fmt::Display for ParseManualError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseManualError::BadDot(line) => write!(f, "Invalid dot '{}'", line),
+            ParseManualError::BadFold(line) => write!(f, "Invalid fold '{}'", line),
+            ParseManualError::MissingFolds => write!(f, "Missing fold instructions")
+        }
     }
 }
+impl std::error::Error for ParseManualError { }
 
-fn apply(y: usize, x: usize, fold: &FoldInstruction) -> (usize, usize) {
-    match *fold {
-        FoldInstruction::FoldX(sx) => (y, if x < sx { x } else { sx-(x-sx) }),
-        FoldInstruction::FoldY(sy) => (if y < sy { y } else { sy-(y-sy) }, x)
+/// Error thrown when folding the paper is not possible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FoldError {
+    /// A dot, given as `(x, y)`, sits right on the fold line
+    DotOnFold((usize, usize))
+}
+impl std::fmt::Display for FoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FoldError::DotOnFold((x, y)) => write!(f, "Dot {},{} is on the fold line", x, y)
+        }
     }
 }
+impl std::error::Error for FoldError { }
 
 /// Solve Advent of Code day 13 part one
 ///
@@ -38,18 +55,8 @@ fn apply(y: usize, x: usize, fold: &FoldInstruction) -> (usize, usize) {
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
 pub fn solve_part_one(data: &str) -> usize {
-    // Get the instructions first
-    let first_instruction: FoldInstruction = fold_from_line(data.split("\n\n").nth(1).unwrap()
-        .split('\n')
-        .next().unwrap());
-    // And now, build the darn data grid
-    data.split("\n\n").next().unwrap().split('\n')
-        .map(|coords| {
-            let decoded = coords.split(',').map(|c| c.parse::<usize>().unwrap()).collect::<Vec<usize>>();
-            apply(*decoded.get(1).unwrap(), *decoded.get(0).unwrap(), &first_instruction)
-        })
-        .collect::<HashSet<(usize, usize)>>()
-        .len()
+    let manual = data.parse::<Manual>().unwrap();
+    manual.paper.fold(manual.folds[0]).unwrap().count()
 }
 
 /// Solve Advent of Code day 13 part two
@@ -68,25 +75,6 @@ pub fn solve_part_one(data: &str) -> usize {
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
 pub fn solve_part_two(data: &str) -> String {
-    let more_instructions: Vec<FoldInstruction> = data.split("\n\n")
-        .nth(1).unwrap()
-        .split('\n')
-        .map(|fold| fold_from_line(fold))
-        .collect::<Vec<FoldInstruction>>();
-    let code: HashSet<(usize, usize)> = data.split("\n\n").next()
-        .unwrap().split('\n')
-        .map(|coords| {
-            let decoded = coords.split(',').map(|c| c.parse::<usize>().unwrap()).collect::<Vec<usize>>();
-            more_instructions.iter()
-                .fold((*decoded.get(1).unwrap(), *decoded.get(0).unwrap()),
-                    |(y, x), i| apply(y, x, i))
-        })
-        .collect::<HashSet<(usize, usize)>>();
-        (0..6)
-            .map(|y| (0..39)
-                 .map(|x| if code.contains(&(y, x)) { '#' } else { ' ' })
-                 .collect::<String>())
-            .collect::<Vec<String>>()
-            .join("\n")
+    let manual = data.parse::<Manual>().unwrap();
+    manual.steps().unwrap().last().unwrap_or(&manual.paper).to_string()
 }
-
@@ -2,12 +2,22 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day13;
+use day13::{solve_part_one, solve_part_two, Manual};
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--steps` prints the paper after every fold
+    if std::env::args().any(|a| a == "--steps") {
+        let manual = data.parse::<Manual>()?;
+        for (fold, paper) in manual.folds.iter().zip(manual.steps()?) {
+            println!("{:?}: {}x{}, {} dots", fold, paper.width(), paper.height(),
+                paper.count());
+            println!("{}\n", paper);
+        }
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -17,13 +27,63 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use day13::{FoldError, FoldInstruction, ParseManualError, Paper};
 
     #[test]
     fn day13_01_example1() {
         let data = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n";
         let expected = 17;
-        assert_eq!(expected, solve_part_one(&data));
+        assert_eq!(expected, solve_part_one(data));
+    }
+
+    #[test]
+    fn day13_02_example1() {
+        let data = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n";
+        let expected = "#####\n#   #\n#   #\n#   #\n#####";
+        assert_eq!(expected, solve_part_two(data));
+    }
+
+    #[test]
+    fn day13_fold_steps() {
+        let data = "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5\n";
+        let manual = data.parse::<Manual>().unwrap();
+        assert_eq!(vec![FoldInstruction::FoldY(7), FoldInstruction::FoldX(5)], manual.folds);
+        assert_eq!((11, 15, 18), (manual.paper.width(), manual.paper.height(), manual.paper.count()));
+        let steps = manual.steps().unwrap();
+        assert_eq!(vec![(11, 7, 17), (5, 7, 16)], steps.iter()
+            .map(|p| (p.width(), p.height(), p.count()))
+            .collect::<Vec<(usize, usize, usize)>>());
+        // Rendering stops at the furthest dot, even if the sheet is larger
+        assert_eq!("# ##  #  # \n#   #      \n      #   #\n#   #      \n # #  # ###",
+            steps[0].to_string());
     }
 
-    // There is no second test here
+    #[test]
+    fn day13_off_midline_folds() {
+        // Folding a tall sheet near its top flips the bottom part past it
+        let paper = Paper::new([(0, 0), (1, 4), (2, 10)].into_iter().collect());
+        let folded = paper.fold(FoldInstruction::FoldY(3)).unwrap();
+        assert_eq!((3, 7), (folded.width(), folded.height()));
+        assert!(folded.contains((0, 4)) && folded.contains((1, 6)) && folded.contains((2, 0)));
+        assert_eq!("  #\n   \n   \n   \n#  \n   \n # ", folded.to_string());
+        // Folding near the bottom keeps the sheet in place
+        let folded = paper.fold(FoldInstruction::FoldY(8)).unwrap();
+        assert_eq!((3, 8), (folded.width(), folded.height()));
+        assert!(folded.contains((0, 0)) && folded.contains((1, 4)) && folded.contains((2, 6)));
+        // Folding twice in the same place changes nothing more
+        let again = folded.fold(FoldInstruction::FoldY(8)).unwrap();
+        assert_eq!(folded, again);
+        assert_eq!(Err(FoldError::DotOnFold((1, 4))), paper.fold(FoldInstruction::FoldY(4)));
+    }
+
+    #[test]
+    fn day13_parse_errors() {
+        assert_eq!(Err(ParseManualError::MissingFolds), "0,0\n1,1".parse::<Manual>());
+        assert_eq!(Err(ParseManualError::BadDot(String::from("0;0"))),
+            "0;0\n\nfold along x=1".parse::<Manual>());
+        assert_eq!(Err(ParseManualError::BadFold(String::from("fold along z=1"))),
+            "0,0\n\nfold along z=1".parse::<Manual>());
+        assert_eq!(Err(ParseManualError::BadFold(String::from("fold along y=-1"))),
+            "0,0\n\nfold along y=-1".parse::<Manual>());
+    }
 }
@@ -0,0 +1,174 @@
+//! Module defining the transparent [`Paper`] and how it folds
+
+use std::collections::HashSet;
+
+use crate::{FoldError, ParseManualError};
+
+/// Position of a dot, as `(x, y)`
+pub type Coords = (usize, usize);
+
+/// A fold of the paper along a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldInstruction {
+    /// Fold the right part over to the left, along a vertical line
+    FoldX(usize),
+    /// Fold the bottom part up, along a horizontal line
+    FoldY(usize)
+}
+
+impl std::str::FromStr for FoldInstruction {
+    type Err = ParseManualError;
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let bad_fold = || ParseManualError::BadFold(line.to_string());
+        let (axis, position) = line.trim().split_once('=').ok_or_else(bad_fold)?;
+        let position = position.parse::<usize>().map_err(|_| bad_fold())?;
+        match axis {
+            "fold along x" => Ok(FoldInstruction::FoldX(position)),
+            "fold along y" => Ok(FoldInstruction::FoldY(position)),
+            _ => Err(bad_fold())
+        }
+    }
+}
+
+/// A sheet of transparent paper with dots on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paper {
+    dots: HashSet<Coords>,
+    width: usize,
+    height: usize
+}
+
+impl Paper {
+    /// A sheet just large enough for its dots
+    #[must_use]
+    pub fn new(dots: HashSet<Coords>) -> Paper {
+        let width = dots.iter().map(|d| d.0 + 1).max().unwrap_or(0);
+        let height = dots.iter().map(|d| d.1 + 1).max().unwrap_or(0);
+        Paper { dots, width, height }
+    }
+
+    /// Width of the sheet
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the sheet
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of visible dots
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.dots.len()
+    }
+
+    /// Is there a dot at a position?
+    #[must_use]
+    pub fn contains(&self, dot: Coords) -> bool {
+        self.dots.contains(&dot)
+    }
+
+    /// Fold the paper
+    ///
+    /// The fold does not have to be on the middle of the sheet. When the
+    /// folded part is the largest, it extends past the edge of the other
+    /// one, and the sheet is shifted so that it starts back at 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FoldError`] if a dot sits on the fold line.
+    pub fn fold(&self, fold: FoldInstruction) -> Result<Paper, FoldError> {
+        let (line, size) = match fold {
+            FoldInstruction::FoldX(x) => (x, self.width),
+            FoldInstruction::FoldY(y) => (y, self.height)
+        };
+        // Rows or columns past the fold, landing before the kept ones
+        let folded = size.saturating_sub(line + 1);
+        let shift = folded.saturating_sub(line);
+        let flip = |v: usize| -> Result<usize, usize> {
+            match v.cmp(&line) {
+                std::cmp::Ordering::Less => Ok(v + shift),
+                std::cmp::Ordering::Equal => Err(v),
+                std::cmp::Ordering::Greater => Ok(line + shift - (v - line))
+            }
+        };
+        let dots = self.dots.iter()
+            .map(|&(x, y)| match fold {
+                FoldInstruction::FoldX(_) => flip(x).map(|x| (x, y)),
+                FoldInstruction::FoldY(_) => flip(y).map(|y| (x, y))
+            }
+            .map_err(|_| FoldError::DotOnFold((x, y))))
+            .collect::<Result<HashSet<Coords>, FoldError>>()?;
+        let size = line.max(folded);
+        Ok(match fold {
+            FoldInstruction::FoldX(_) => Paper { dots, width: size, height: self.height },
+            FoldInstruction::FoldY(_) => Paper { dots, width: self.width, height: size }
+        })
+    }
+}
+
+/// Draw the dots as `#`, from the top left corner of the sheet to the
+/// furthest dot
+impl std::fmt::Display for Paper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.dots.iter().map(|d| d.0 + 1).max().unwrap_or(0);
+        let height = self.dots.iter().map(|d| d.1 + 1).max().unwrap_or(0);
+        for y in 0..height {
+            if y > 0 { writeln!(f)?; }
+            let row = (0..width)
+                .map(|x| if self.dots.contains(&(x, y)) { '#' } else { ' ' })
+                .collect::<String>();
+            write!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// The dots and the folds of the instruction manual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manual {
+    /// The unfolded paper
+    pub paper: Paper,
+    /// Every fold, in order
+    pub folds: Vec<FoldInstruction>
+}
+
+impl Manual {
+    /// Fold the paper, step by step
+    ///
+    /// # Return Value
+    ///
+    /// Returns the paper after every fold, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FoldError`] if a dot sits on a fold line.
+    pub fn steps(&self) -> Result<Vec<Paper>, FoldError> {
+        let mut papers: Vec<Paper> = Vec::with_capacity(self.folds.len());
+        for &fold in &self.folds {
+            let paper = papers.last().unwrap_or(&self.paper).fold(fold)?;
+            papers.push(paper);
+        }
+        Ok(papers)
+    }
+}
+
+impl std::str::FromStr for Manual {
+    type Err = ParseManualError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let (dots, folds) = data.trim().split_once("\n\n")
+            .ok_or(ParseManualError::MissingFolds)?;
+        let dots = dots.lines()
+            .map(|line| line.split_once(',')
+                .and_then(|(x, y)| Some((x.parse::<usize>().ok()?, y.parse::<usize>().ok()?)))
+                .ok_or_else(|| ParseManualError::BadDot(line.to_string())))
+            .collect::<Result<HashSet<Coords>, ParseManualError>>()?;
+        let folds = folds.lines()
+            .map(str::parse::<FoldInstruction>)
+            .collect::<Result<Vec<FoldInstruction>, ParseManualError>>()?;
+        Ok(Manual { paper: Paper::new(dots), folds })
+    }
+}
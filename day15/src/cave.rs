@@ -0,0 +1,349 @@
+//! Module defining the [`Cave`] and searching it for the safest path
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::ParseCaveError;
+
+/// Position in the cave, as `(x, y)`
+pub type Coords = (usize, usize);
+
+/// How the shortest path is searched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Search {
+    /// Plain Dijkstra, expanding positions by increasing risk
+    #[default]
+    Dijkstra,
+    /// A*, also favouring positions close to the end
+    AStar
+}
+
+/// Positions reachable in a single move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Moves {
+    /// Up, down, left and right
+    #[default]
+    Orthogonal,
+    /// The four orthogonal moves, and the four diagonal ones
+    Diagonal
+}
+
+impl Moves {
+    /// Offsets of the moves, as `(dx, dy)`
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Moves::Orthogonal => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Moves::Diagonal => &[(-1, -1), (0, -1), (1, -1), (-1, 0),
+                (1, 0), (-1, 1), (0, 1), (1, 1)]
+        }
+    }
+
+    /// Fewest moves between two positions
+    fn distance(self, (x, y): Coords, (ex, ey): Coords) -> usize {
+        let (dx, dy) = (x.abs_diff(ex), y.abs_diff(ey));
+        match self {
+            Moves::Orthogonal => dx + dy,
+            Moves::Diagonal => dx.max(dy)
+        }
+    }
+}
+
+/// Cost of a move, from the risk levels of both positions
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Cost {
+    /// The risk of the position entered, as in the puzzle
+    #[default]
+    Entering,
+    /// The risk of the position left
+    Leaving,
+    /// How much the risk level changes, as if it were a height
+    HeightDifference,
+    /// Any function of the risk levels of the positions left and entered
+    Custom(fn(u8, u8) -> usize)
+}
+
+impl Cost {
+    /// Cost of moving from a position to another
+    #[must_use]
+    pub fn apply(self, from: u8, to: u8) -> usize {
+        match self {
+            Cost::Entering => to as usize,
+            Cost::Leaving => from as usize,
+            Cost::HeightDifference => from.abs_diff(to) as usize,
+            Cost::Custom(cost) => cost(from, to)
+        }
+    }
+
+    /// Cheapest possible move
+    fn cheapest(self) -> usize {
+        (1..=9).flat_map(|from| (1..=9).map(move |to| self.apply(from, to)))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// How to move through the cave
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rules {
+    /// Positions reachable in a single move
+    pub moves: Moves,
+    /// Cost of every move
+    pub cost: Cost
+}
+
+/// A path through the cave, with its total risk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// Total cost of the moves of the path
+    pub risk: usize,
+    /// Every position on the path, from start to end
+    pub cells: Vec<Coords>
+}
+
+/// Rectangular cave, with the risk level of every position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cave {
+    width: usize,
+    height: usize,
+    /// Risk levels, row by row
+    risks: Vec<u8>
+}
+
+impl Cave {
+    /// Width of the cave
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the cave
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Bottom right position of the cave
+    #[must_use]
+    pub fn end(&self) -> Coords {
+        (self.width - 1, self.height - 1)
+    }
+
+    /// Risk level of a position
+    #[must_use]
+    pub fn risk(&self, (x, y): Coords) -> u8 {
+        self.risks[y * self.width + x]
+    }
+
+    /// The full cave, made of `factor` by `factor` copies of this one
+    ///
+    /// Every copy to the right or below has its risk levels increased by
+    /// one, wrapping back to 1 after 9.
+    ///
+    /// # Return Value
+    ///
+    /// Returns the full cave, or `None` if `factor` is 0, as a cave cannot
+    /// be empty.
+    #[must_use]
+    pub fn tiled(&self, factor: usize) -> Option<Cave> {
+        if factor == 0 { return None; }
+        let (width, height) = (self.width * factor, self.height * factor);
+        let risks = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let base = self.risk((x % self.width, y % self.height)) as usize;
+                ((base - 1 + x / self.width + y / self.height) % 9 + 1) as u8
+            })
+            .collect::<Vec<u8>>();
+        Some(Cave { width, height, risks })
+    }
+
+    /// Is a position inside of the cave?
+    fn contains(&self, (x, y): Coords) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Positions reachable from a position in a single move
+    fn neighbours(&self, (x, y): Coords, moves: Moves) -> impl Iterator<Item = Coords> + '_ {
+        moves.offsets().iter()
+            .filter_map(move |&(dx, dy)| {
+                let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                self.contains(next).then_some(next)
+            })
+    }
+
+    /// Find the path of lowest total risk between two positions, with the
+    /// moves of the puzzle
+    ///
+    /// # Arguments
+    ///
+    ///  - `start` : the starting position, whose risk is not counted.
+    ///  - `end` : the position to reach.
+    ///  - `search` : the [`Search`] algorithm to use. Both always find a
+    ///    path of lowest risk. A* expands fewer positions, but since risk
+    ///    levels are much larger than its estimate on the puzzle input, it
+    ///    ends up a little slower there.
+    ///
+    /// # Return Value
+    ///
+    /// Returns the [`Path`], or `None` if either position is outside of the
+    /// cave.
+    #[must_use]
+    pub fn shortest_path(&self, start: Coords, end: Coords, search: Search) -> Option<Path> {
+        self.shortest_path_with(start, end, search, Rules::default())
+    }
+
+    /// Find the path of lowest total cost between two positions, with any
+    /// [`Rules`]
+    ///
+    /// The A* estimate is scaled down to the cheapest move the cost allows,
+    /// so both searches still find a path of lowest cost.
+    #[must_use]
+    pub fn shortest_path_with(&self, start: Coords, end: Coords, search: Search, rules: Rules)
+        -> Option<Path> {
+        if !self.contains(start) || !self.contains(end) {
+            return None;
+        }
+        let map = self.explore(start, Some((end, search)), rules);
+        map.path_to(end)
+    }
+
+    /// Find the paths of lowest total cost from a position to all others
+    ///
+    /// # Return Value
+    ///
+    /// Returns the [`RiskMap`] of the cave, or `None` if `start` is outside of
+    /// the cave.
+    #[must_use]
+    pub fn risk_map(&self, start: Coords, rules: Rules) -> Option<RiskMap> {
+        self.contains(start).then(|| self.explore(start, None, rules))
+    }
+
+    /// Find the paths of lowest total cost between many pairs of positions
+    ///
+    /// Every starting position is only explored once, whatever the number of
+    /// ends it is paired with.
+    ///
+    /// # Return Value
+    ///
+    /// Returns a [`Path`] for every pair, in order, or `None` if either
+    /// position is outside of the cave.
+    #[must_use]
+    pub fn shortest_paths(&self, pairs: &[(Coords, Coords)], rules: Rules) -> Vec<Option<Path>> {
+        let mut maps: HashMap<Coords, Option<RiskMap>> = HashMap::new();
+        pairs.iter()
+            .map(|&(start, end)| maps.entry(start)
+                .or_insert_with(|| self.risk_map(start, rules))
+                .as_ref()
+                .and_then(|map| map.path_to(end)))
+            .collect::<Vec<Option<Path>>>()
+    }
+
+    /// Explore the cave from a position, until reaching the target if any
+    fn explore(&self, start: Coords, target: Option<(Coords, Search)>, rules: Rules) -> RiskMap {
+        let cheapest = rules.cost.cheapest();
+        let heuristic = |position: Coords| match target {
+            Some((end, Search::AStar)) => rules.moves.distance(position, end) * cheapest,
+            _ => 0
+        };
+        let index = |(x, y): Coords| y * self.width + x;
+        let mut risks = vec![usize::MAX; self.risks.len()];
+        let mut previous = vec![usize::MAX; self.risks.len()];
+        let mut queue = BinaryHeap::new();
+        risks[index(start)] = 0;
+        queue.push(Reverse((heuristic(start), 0, start)));
+        while let Some(Reverse((_, risk, position))) = queue.pop() {
+            if target.is_some_and(|(end, _)| position == end) { break; }
+            if risk > risks[index(position)] { continue; }
+            for next in self.neighbours(position, rules.moves) {
+                let next_risk = risk + rules.cost.apply(self.risk(position), self.risk(next));
+                if next_risk < risks[index(next)] {
+                    risks[index(next)] = next_risk;
+                    previous[index(next)] = index(position);
+                    queue.push(Reverse((next_risk + heuristic(next), next_risk, next)));
+                }
+            }
+        }
+        RiskMap { width: self.width, start: index(start), risks, previous }
+    }
+
+    /// Draw a path over the cave
+    ///
+    /// Positions on the path show their risk level, all others are `.`.
+    #[must_use]
+    pub fn render(&self, path: &Path) -> String {
+        let mut on_path = vec![false; self.risks.len()];
+        for &(x, y) in &path.cells {
+            on_path[y * self.width + x] = true;
+        }
+        let mut drawing = String::with_capacity((self.width + 1) * self.height);
+        for (i, &risk) in self.risks.iter().enumerate() {
+            if i > 0 && i % self.width == 0 { drawing.push('\n'); }
+            drawing.push(if on_path[i] { (b'0' + risk) as char } else { '.' });
+        }
+        drawing
+    }
+}
+
+/// Lowest total cost from a starting position to the others, with the way
+/// back
+#[derive(Debug, Clone)]
+pub struct RiskMap {
+    width: usize,
+    start: usize,
+    /// Lowest total cost of every position, `usize::MAX` when not reached
+    risks: Vec<usize>,
+    /// Position before every position on its path
+    previous: Vec<usize>
+}
+
+impl RiskMap {
+    /// Lowest total cost of reaching a position, if it was reached
+    #[must_use]
+    pub fn risk(&self, (x, y): Coords) -> Option<usize> {
+        if x >= self.width { return None; }
+        self.risks.get(y * self.width + x).copied().filter(|&r| r != usize::MAX)
+    }
+
+    /// The path of lowest total cost to a position, if it was reached
+    #[must_use]
+    pub fn path_to(&self, end: Coords) -> Option<Path> {
+        let risk = self.risk(end)?;
+        let mut cells = vec![end];
+        let mut current = end.1 * self.width + end.0;
+        while current != self.start {
+            current = self.previous[current];
+            cells.push((current % self.width, current / self.width));
+        }
+        cells.reverse();
+        Some(Path { risk, cells })
+    }
+}
+
+impl std::fmt::Display for Cave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.risks.chunks(self.width) {
+            writeln!(f, "{}", row.iter().map(|&r| (b'0' + r) as char).collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Cave {
+    type Err = ParseCaveError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let lines = data.trim().split('\n').collect::<Vec<&str>>();
+        let width = lines[0].chars().count();
+        if width == 0 { return Err(ParseCaveError::Empty); }
+        let mut risks = Vec::with_capacity(width * lines.len());
+        for line in &lines {
+            if line.chars().count() != width { return Err(ParseCaveError::RaggedRows); }
+            for c in line.chars() {
+                match c.to_digit(10) {
+                    Some(r) if r > 0 => risks.push(r as u8),
+                    _ => return Err(ParseCaveError::UnknownRisk(c))
+                }
+            }
+        }
+        Ok(Cave { width, height: lines.len(), risks })
+    }
+}
@@ -0,0 +1,156 @@
+//! Module finding the lowest total risk with a [`petgraph`] graph
+//!
+//! Every position of the cave gets a node, with two edges between
+//! neighbours, and petgraph's Dijkstra does the rest. The tests check that
+//! [`Cave`] paths have the same risk, and the `graph` benchmark is the
+//! baseline the grid searches are compared against.
+//!
+//! [`Cave`]: crate::cave::Cave
+use petgraph::prelude::*;
+use petgraph::algo::dijkstra;
+
+use std::collections::HashMap;
+
+type UndirGraph = Graph<(usize, usize), usize>;
+type NodeDict = HashMap<(usize, usize), NodeIndex>;
+
+/// Lowest total risk through the cave, with a graph
+///
+/// # Panics
+///
+/// If any conversion assumed to be valid with the input fails, panics.
+#[must_use]
+pub fn graph_lowest_risk(data: &str) -> usize {
+    // Build a grid of data
+    let grid: HashMap<(usize, usize), usize> = build_grid(data);
+    let (height, width) = cave_size(data);
+    // Build a graph
+    let (graph, dic) = build_graph(&grid);
+    let start: NodeIndex = *dic.get(&(0, 0)).unwrap();
+    let end: NodeIndex = *dic.get(&(height-1, width-1)).unwrap();
+    let res = dijkstra(&graph,
+                       start, // Start
+                       Some(end), // End
+            |e| *e.weight());
+    *res.get(&end).unwrap()
+}
+
+/// Number of rows and columns of the cave
+fn cave_size(data: &str) -> (usize, usize) {
+    let lines = data.trim().split('\n').collect::<Vec<&str>>();
+    (lines.len(), lines[0].len())
+}
+
+/// Read the risk of every position, as `(y, x)`
+fn build_grid(data: &str) -> HashMap<(usize, usize), usize> {
+    data.trim().split('\n')
+        .enumerate()
+        .flat_map(|(y, line)| line.chars()
+             .enumerate()
+             .map(move |(x, c)|
+                  ((y, x), String::from(c).parse::<usize>().unwrap())
+            ))
+        .collect::<HashMap<(usize, usize), usize>>()
+}
+
+fn build_graph(data: &HashMap<(usize, usize), usize>) -> (UndirGraph, NodeDict) {
+    // Build this graph
+    let mut res: UndirGraph = Graph::new();
+    // Build the dict of nodes
+    let nodes = data.keys()
+        .map(|&(y, x)| ((y, x), res.add_node((y, x))))
+        .collect::<NodeDict>();
+    // For all coordinates, create an edge from its possible upstream
+    let extendable_edges = data.iter()
+        .flat_map(|(&(y, x), &v)| {
+            let mut r = Vec::new();
+            let me: NodeIndex = *nodes.get(&(y, x)).unwrap();
+            // Possible up?
+            if y > 0 {
+                let topnode = *nodes.get(&(y-1, x)).unwrap();
+                let topvalue = *data.get(&(y-1, x)).unwrap();
+                r.push((topnode, me, v));
+                r.push((me, topnode, topvalue));
+            }
+            if x > 0 {
+                let botnode = *nodes.get(&(y, x-1)).unwrap();
+                let botvalue = *data.get(&(y, x-1)).unwrap();
+                r.push((botnode, me, v));
+                r.push((me, botnode, botvalue));
+            }
+            r
+        })
+        .collect::<Vec<(NodeIndex, NodeIndex, usize)>>();
+    res.extend_with_edges(extendable_edges);
+    (res, nodes)
+}
+
+/// Lowest total risk through the cave tiled 5 times, with a graph
+///
+/// # Panics
+///
+/// If any conversion assumed to be valid with the input fails, panics.
+#[must_use]
+pub fn graph_lowest_risk_tiled(data: &str) -> usize {
+    // Build a grid of data
+    let grid: HashMap<(usize, usize), usize> = build_grid(data);
+    let (height, width) = cave_size(data);
+    // Build a graph
+    let (graph, dic) = build_large_graph(&grid, height, width);
+    let start: NodeIndex = *dic.get(&(0, 0)).unwrap();
+    let end: NodeIndex = *dic.get(&(5*height-1, 5*width-1)).unwrap();
+    let res = dijkstra(&graph,
+                       start, // Start
+                       Some(end), // End
+            |e| *e.weight());
+    *res.get(&end).unwrap()
+}
+
+fn enlarge_risk_value(y: usize, x: usize, y_max: usize, x_max: usize, val: usize) -> usize {
+    (val - 1 + (y/y_max) + (x/x_max))%9+1
+}
+
+fn build_large_graph(data: &HashMap<(usize, usize), usize>, y_size: usize, x_size: usize) -> (UndirGraph, NodeDict) {
+    // Build this graph
+    let mut res: UndirGraph = Graph::new();
+    // Build the dict of nodes
+    let nodes = data.keys()
+        .flat_map(|&(y, x)|
+              (0..5).flat_map(|dy| {
+                  let my_y = dy*y_size+y;
+                vec![
+                    ((my_y, x), res.add_node((dy*y_size+y, x))),
+                    ((my_y, x_size+x), res.add_node((dy*y_size+y, x_size+x))),
+                    ((my_y, 2*x_size+x), res.add_node((my_y, 2*x_size+x))),
+                    ((my_y, 3*x_size+x), res.add_node((my_y, 3*x_size+x))),
+                    ((my_y, 4*x_size+x), res.add_node((my_y, 4*x_size+x)))
+                ]
+            })
+            .collect::<Vec<((usize, usize), NodeIndex)>>()
+        )
+        .collect::<NodeDict>();
+    // For all coordinates, create an edge from its possible upstream
+    let extendable_edges = nodes.iter()
+        .flat_map(|(&(y, x), &me)| {
+            let mut r = Vec::new();
+            let v = enlarge_risk_value(y, x, y_size, x_size, *data.get(&(y%y_size, x%x_size)).unwrap());
+            // Possible up?
+            if y > 0 {
+                let topnode = *nodes.get(&(y-1, x)).unwrap();
+                let topvalue = enlarge_risk_value(y-1, x, y_size, x_size, *data.get(&((y-1)%y_size, x%x_size)).unwrap());
+                r.push((topnode, me, v));
+                r.push((me, topnode, topvalue));
+            }
+            if x > 0 {
+                let botnode = *nodes.get(&(y, x-1)).unwrap();
+                let botvalue = enlarge_risk_value(y, x-1, y_size, x_size, *data.get(&(y%y_size, (x-1)%x_size)).unwrap());
+                r.push((botnode, me, v));
+                r.push((me, botnode, botvalue));
+            }
+            r
+        })
+        .collect::<Vec<(NodeIndex, NodeIndex, usize)>>();
+    res.extend_with_edges(extendable_edges);
+    (res, nodes)
+}
+
@@ -1,11 +1,41 @@
 //! Library module with all the logic
-use petgraph::prelude::*;
-use petgraph::algo::dijkstra;
+pub mod cave;
+pub mod graph;
+pub use cave::{Cave, Coords, Cost, Moves, Path, RiskMap, Rules, Search};
+pub use graph::{graph_lowest_risk, graph_lowest_risk_tiled};
 
-use std::collections::HashMap;
+/// Error thrown when reading an incorrect cave
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCaveError {
+    /// The cave has no positions
+    Empty,
+    /// A risk level is not a digit from 1 to 9
+    UnknownRisk(char),
+    /// Rows of the cave do not all have the same width
+    RaggedRows
+}
+impl std::fmt::Display for ParseCaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCaveError::Empty => write!(f, "Empty cave"),
+            ParseCaveError::UnknownRisk(c) => write!(f, "Unknown risk level {:?}", c),
+            ParseCaveError::RaggedRows => write!(f, "Cave rows have different widths")
+        }
+    }
+}
+impl std::error::Error for ParseCaveError { }
 
-type UndirGraph = Graph<(usize, usize), usize>;
-type NodeDict = HashMap<(usize, usize), NodeIndex>;
+/// Lowest total risk from the top left to the bottom right of a cave
+///
+/// # Panics
+///
+/// If any conversion assumed to be valid with the input fails, or if
+/// `tiles` is 0, panics.
+#[must_use]
+pub fn lowest_risk(data: &str, tiles: usize) -> usize {
+    let cave = data.parse::<Cave>().unwrap().tiled(tiles).expect("at least one tile");
+    cave.shortest_path((0, 0), cave.end(), Search::Dijkstra).unwrap().risk
+}
 
 /// Solve Advent of Code day 15 part one
 ///
@@ -23,62 +53,7 @@ type NodeDict = HashMap<(usize, usize), NodeIndex>;
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
 pub fn solve_part_one(data: &str) -> usize {
-    // Build a grid of data
-    let grid: HashMap<(usize, usize), usize> = build_grid(data);
-    let x_max = data.trim().split('\n').count();
-    let y_max = data.trim().split('\n').next().unwrap().len();
-    // Build a graph
-    let (graph, dic) = build_graph(&grid);
-    let start: NodeIndex = *dic.get(&(0, 0)).unwrap();
-    let end: NodeIndex = *dic.get(&(y_max-1, x_max-1)).unwrap();
-    let res = dijkstra(&graph,
-                       start, // Start
-                       Some(end), // End
-            |e| *e.weight());
-    *res.get(&end).unwrap()
-}
-
-fn build_grid(data: &str) -> HashMap<(usize, usize), usize> {
-    data.trim().split('\n')
-        .enumerate()
-        .flat_map(|(x, line)| line.chars()
-             .enumerate()
-             .map(move |(y, c)|
-                  ((y, x), String::from(c).parse::<usize>().unwrap())
-            ))
-        .collect::<HashMap<(usize, usize), usize>>()
-}
-
-fn build_graph(data: &HashMap<(usize, usize), usize>) -> (UndirGraph, NodeDict) {
-    // Build this graph
-    let mut res: UndirGraph = Graph::new();
-    // Build the dict of nodes
-    let nodes = data.keys()
-        .map(|&(y, x)| ((y, x), res.add_node((y, x))))
-        .collect::<NodeDict>();
-    // For all coordinates, create an edge from its possible upstream
-    let extendable_edges = data.iter()
-        .flat_map(|(&(y, x), &v)| {
-            let mut r = Vec::new();
-            let me: NodeIndex = *nodes.get(&(y, x)).unwrap();
-            // Possible up?
-            if y > 0 {
-                let topnode = *nodes.get(&(y-1, x)).unwrap();
-                let topvalue = *data.get(&(y-1, x)).unwrap();
-                r.push((topnode, me, v));
-                r.push((me, topnode, topvalue));
-            }
-            if x > 0 {
-                let botnode = *nodes.get(&(y, x-1)).unwrap();
-                let botvalue = *data.get(&(y, x-1)).unwrap();
-                r.push((botnode, me, v));
-                r.push((me, botnode, botvalue));
-            }
-            r
-        })
-        .collect::<Vec<(NodeIndex, NodeIndex, usize)>>();
-    res.extend_with_edges(extendable_edges);
-    (res, nodes)
+    lowest_risk(data, 1)
 }
 
 /// Solve Advent of Code day 15 part two
@@ -97,66 +72,5 @@ fn build_graph(data: &HashMap<(usize, usize), usize>) -> (UndirGraph, NodeDict)
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
 pub fn solve_part_two(data: &str) -> usize {
-    // Build a grid of data
-    let grid: HashMap<(usize, usize), usize> = build_grid(data);
-    let x_max = data.trim().split('\n').count();
-    let y_max = data.trim().split('\n').next().unwrap().len();
-    // Build a graph
-    let (graph, dic) = build_large_graph(&grid, y_max, x_max);
-    let start: NodeIndex = *dic.get(&(0, 0)).unwrap();
-    let end: NodeIndex = *dic.get(&(5*y_max-1, 5*x_max-1)).unwrap();
-    let res = dijkstra(&graph,
-                       start, // Start
-                       Some(end), // End
-            |e| *e.weight());
-    *res.get(&end).unwrap()
-}
-
-fn enlarge_risk_value(y: usize, x: usize, x_max: usize, y_max: usize, val: usize) -> usize {
-    (val - 1 + (x/x_max) + (y/y_max))%9+1
-}
-
-fn build_large_graph(data: &HashMap<(usize, usize), usize>, y_size: usize, x_size: usize) -> (UndirGraph, NodeDict) {
-    // Build this graph
-    let mut res: UndirGraph = Graph::new();
-    // Build the dict of nodes
-    let nodes = data.keys()
-        .flat_map(|&(y, x)|
-              (0..5).flat_map(|dy| {
-                  let my_y = dy*y_size+y;
-                vec![
-                    ((my_y, x), res.add_node((dy*y_size+y, x))),
-                    ((my_y, x_size+x), res.add_node((dy*y_size+y, x_size+x))),
-                    ((my_y, 2*x_size+x), res.add_node((my_y, 2*x_size+x))),
-                    ((my_y, 3*x_size+x), res.add_node((my_y, 3*x_size+x))),
-                    ((my_y, 4*x_size+x), res.add_node((my_y, 4*x_size+x)))
-                ]
-            })
-            .collect::<Vec<((usize, usize), NodeIndex)>>()
-        )
-        .collect::<NodeDict>();
-    // For all coordinates, create an edge from its possible upstream
-    let extendable_edges = nodes.iter()
-        .flat_map(|(&(y, x), &me)| {
-            let mut r = Vec::new();
-            let v = enlarge_risk_value(y, x, y_size, x_size, *data.get(&(y%y_size, x%x_size)).unwrap());
-            // Possible up?
-            if y > 0 {
-                let topnode = *nodes.get(&(y-1, x)).unwrap();
-                let topvalue = enlarge_risk_value(y-1, x, y_size, x_size, *data.get(&((y-1)%y_size, x%x_size)).unwrap());
-                r.push((topnode, me, v));
-                r.push((me, topnode, topvalue));
-            }
-            if x > 0 {
-                let botnode = *nodes.get(&(y, x-1)).unwrap();
-                let botvalue = enlarge_risk_value(y, x-1, y_size, x_size, *data.get(&(y%y_size, (x-1)%x_size)).unwrap());
-                r.push((botnode, me, v));
-                r.push((me, botnode, botvalue));
-            }
-            r
-        })
-        .collect::<Vec<(NodeIndex, NodeIndex, usize)>>();
-    res.extend_with_edges(extendable_edges);
-    (res, nodes)
+    lowest_risk(data, 5)
 }
-
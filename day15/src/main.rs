@@ -2,12 +2,48 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day15;
+use day15::{solve_part_one, solve_part_two, Cave, Coords, Cost, Moves, Rules, Search};
+
+/// Read the position given after an option, as `x,y`
+fn coords_from_args(option: &str) -> Result<Option<Coords>, Box<dyn std::error::Error>> {
+    let Some(value) = std::env::args().skip_while(|a| a != option).nth(1) else {
+        return Ok(None);
+    };
+    let (x, y) = value.split_once(',').ok_or("expected a position as <x>,<y>")?;
+    Ok(Some((x.parse::<usize>()?, y.parse::<usize>()?)))
+}
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--path [tiles]` draws the safest path through the cave, from `--from
+    // <x>,<y>` to `--to <x>,<y>`, moving diagonally too with `--diagonal`,
+    // with moves costing `--cost entering|leaving|height`
+    let mut args = std::env::args().skip_while(|a| a != "--path");
+    if args.next().is_some() {
+        let tiles = args.next().filter(|t| !t.starts_with("--"))
+            .map_or(Ok(1), |t| t.parse::<usize>())?;
+        let cave = data.parse::<Cave>()?.tiled(tiles).ok_or("expected at least one tile")?;
+        let moves = if std::env::args().any(|a| a == "--diagonal") {
+            Moves::Diagonal
+        } else {
+            Moves::Orthogonal
+        };
+        let cost = match std::env::args().skip_while(|a| a != "--cost").nth(1).as_deref() {
+            None | Some("entering") => Cost::Entering,
+            Some("leaving") => Cost::Leaving,
+            Some("height") => Cost::HeightDifference,
+            _ => return Err("expected --cost entering, leaving or height".into())
+        };
+        let start = coords_from_args("--from")?.unwrap_or((0, 0));
+        let end = coords_from_args("--to")?.unwrap_or(cave.end());
+        let path = cave.shortest_path_with(start, end, Search::Dijkstra, Rules { moves, cost })
+            .ok_or("no path through the cave")?;
+        println!("{}", cave.render(&path));
+        println!("{}", path.risk);
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -22,13 +58,126 @@ mod test {
     fn day15_01_example1() {
         let data = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
         let expected = 40;
-        assert_eq!(expected, solve_part_one(&data));
+        assert_eq!(expected, solve_part_one(data));
     }
 
     #[test]
     fn day15_02_example1() {
         let data = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
         let expected = 315;
-        assert_eq!(expected, solve_part_two(&data));
+        assert_eq!(expected, solve_part_two(data));
+    }
+
+    /// Lowest total cost, relaxing every move until nothing changes
+    fn relaxed(cave: &Cave, start: Coords, end: Coords, rules: Rules) -> usize {
+        let (width, height) = (cave.width(), cave.height());
+        let mut costs = vec![vec![usize::MAX; width]; height];
+        costs[start.1][start.0] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (y, x) in (0..height).flat_map(|y| (0..width).map(move |x| (y, x))) {
+                for (ny, nx) in (y.saturating_sub(1)..=y + 1).flat_map(|ny|
+                    (x.saturating_sub(1)..=x + 1).map(move |nx| (ny, nx))) {
+                    let diagonal = ny != y && nx != x;
+                    if ny >= height || nx >= width || (ny, nx) == (y, x) || costs[ny][nx] == usize::MAX
+                        || (diagonal && rules.moves == Moves::Orthogonal) { continue; }
+                    let cost = costs[ny][nx] + rules.cost.apply(cave.risk((nx, ny)), cave.risk((x, y)));
+                    if cost < costs[y][x] {
+                        costs[y][x] = cost;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        costs[end.1][end.0]
+    }
+
+    #[test]
+    fn day15_grid_search() {
+        let data = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+        let cave = data.parse::<Cave>().unwrap();
+        let path = cave.shortest_path((0, 0), cave.end(), Search::Dijkstra).unwrap();
+        assert_eq!(40, path.risk);
+        assert_eq!(path.risk, path.cells.iter().skip(1).map(|&c| cave.risk(c) as usize).sum());
+        assert_eq!("1.........\n1.........\n2136511...\n......15..\n.......1..\n.......13.\n........2.\n........3.\n........21\n.........1",
+            cave.render(&path));
+        let large = cave.tiled(5).unwrap();
+        assert_eq!((50, 50), (large.width(), large.height()));
+        assert_eq!("67554889357866599146897761125791887223681299833479",
+            large.to_string().lines().last().unwrap());
+        for search in [Search::Dijkstra, Search::AStar] {
+            assert_eq!(Some(315), large.shortest_path((0, 0), large.end(), search).map(|p| p.risk));
+        }
+        assert_eq!((40, 315), (day15::graph_lowest_risk(data), day15::graph_lowest_risk_tiled(data)));
+        assert_eq!(Some(0), cave.shortest_path((3, 3), (3, 3), Search::AStar).map(|p| p.risk));
+        assert_eq!(None, cave.shortest_path((0, 0), (10, 0), Search::AStar));
+        assert!("120\n111".parse::<Cave>().is_err());
+        assert!("12\n111".parse::<Cave>().is_err());
+    }
+
+    #[test]
+    fn day15_rectangular_caves() {
+        let example = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+        let wide = example.lines().take(4).collect::<Vec<&str>>().join("\n");
+        let tall = example.lines().map(|l| &l[..3]).collect::<Vec<&str>>().join("\n");
+        for data in [wide.as_str(), tall.as_str(), "1111\n9991", "1\n2\n3"] {
+            let cave = data.parse::<Cave>().unwrap();
+            let large = cave.tiled(5).unwrap();
+            assert_eq!((5 * cave.width(), 5 * cave.height()), (large.width(), large.height()));
+            let path = cave.shortest_path((0, 0), cave.end(), Search::Dijkstra).unwrap();
+            let large_path = large.shortest_path((0, 0), large.end(), Search::AStar).unwrap();
+            assert_eq!(path.risk, day15::graph_lowest_risk(data));
+            assert_eq!(large_path.risk, day15::graph_lowest_risk_tiled(data));
+            assert_eq!((path.risk, large_path.risk), (solve_part_one(data), solve_part_two(data)));
+        }
+        assert_eq!(4, solve_part_one("1111\n9991"));
+        // Every tile to the right or below is one more risky
+        assert_eq!("12\n23\n34\n23\n34\n45\n",
+            "1\n2\n3".parse::<Cave>().unwrap().tiled(2).unwrap().to_string());
+        assert_eq!(None, "1\n2\n3".parse::<Cave>().unwrap().tiled(0));
+    }
+
+    #[test]
+    fn day15_movement_rules() {
+        let data = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+        let cave = data.parse::<Cave>().unwrap();
+        let costs = [Cost::Entering, Cost::Leaving, Cost::HeightDifference,
+            Cost::Custom(|from, to| (from as usize * to as usize) % 7)];
+        for (moves, cost) in [Moves::Orthogonal, Moves::Diagonal].into_iter()
+            .flat_map(|m| costs.iter().map(move |&c| (m, c))) {
+            let rules = Rules { moves, cost };
+            for (start, end) in [((0, 0), (9, 9)), ((7, 2), (1, 8)), ((4, 9), (4, 0))] {
+                let expected = relaxed(&cave, start, end, rules);
+                for search in [Search::Dijkstra, Search::AStar] {
+                    let path = cave.shortest_path_with(start, end, search, rules).unwrap();
+                    assert_eq!(expected, path.risk, "{:?} {:?} {:?}", rules, start, end);
+                    assert_eq!((Some(&start), Some(&end)), (path.cells.first(), path.cells.last()));
+                    let total = path.cells.windows(2)
+                        .map(|w| cost.apply(cave.risk(w[0]), cave.risk(w[1])))
+                        .sum::<usize>();
+                    assert_eq!(path.risk, total);
+                }
+            }
+        }
+        // Leaving is entering backward
+        let leaving = Rules { cost: Cost::Leaving, ..Rules::default() };
+        assert_eq!(cave.shortest_path((9, 9), (0, 0), Search::AStar).map(|p| p.risk),
+            cave.shortest_path_with((0, 0), (9, 9), Search::AStar, leaving).map(|p| p.risk));
+        // Counting moves
+        let steps = Rules { moves: Moves::Diagonal, cost: Cost::Custom(|_, _| 1) };
+        assert_eq!(Some(9), cave.shortest_path_with((0, 0), (9, 9), Search::AStar, steps)
+            .map(|p| p.risk));
+        // Batches only explore every start once
+        let pairs = [((0, 0), (9, 9)), ((3, 4), (0, 0)), ((0, 0), (5, 5)), ((10, 0), (0, 0)),
+            ((0, 0), (0, 10))];
+        let paths = cave.shortest_paths(&pairs, Rules::default());
+        for (&(start, end), path) in pairs.iter().zip(&paths) {
+            assert_eq!(cave.shortest_path(start, end, Search::Dijkstra), *path);
+        }
+        assert_eq!(vec![true, true, true, false, false],
+            paths.iter().map(Option::is_some).collect::<Vec<bool>>());
+        let map = cave.risk_map((0, 0), Rules::default()).unwrap();
+        assert_eq!((Some(40), None), (map.risk((9, 9)), map.risk((10, 0))));
     }
 }
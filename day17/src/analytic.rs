@@ -0,0 +1,160 @@
+//! Module finding all of the velocities that hit a [`Target`]
+//!
+//! Both axes are independent: after `t` steps, a probe launched at
+//! `(vx, vy)` is at height `t * vy - t * (t - 1) / 2`, and drag stops its
+//! sideways motion after `|vx|` steps. For every horizontal velocity, the
+//! steps spent inside of the columns of the target form a single range,
+//! and for every vertical velocity, the steps spent inside of its rows
+//! form at most two ranges, one going up and one going down. A velocity
+//! hits if those ranges overlap, which needs no simulation at all.
+
+use crate::target::{Coords, Target};
+
+/// Error returned when the velocities hitting a target cannot be listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrickShotError {
+    /// The target holds the launch height and a position where the probe
+    /// can stop moving sideways, so shooting as high as wanted always
+    /// hits it when falling back
+    Unbounded
+}
+impl std::fmt::Display for TrickShotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrickShotError::Unbounded =>
+                write!(f, "Infinitely many velocities hit the target")
+        }
+    }
+}
+impl std::error::Error for TrickShotError { }
+
+/// Inclusive range of steps, with no end if it goes on forever
+type Steps = (isize, Option<isize>);
+
+/// Find the first step of `start..=end` for which `pred` holds
+///
+/// `pred` must be false then true along the range. Without an `end`, it
+/// must hold eventually.
+fn first_step(start: isize, end: Option<isize>, pred: impl Fn(isize) -> bool)
+    -> Option<isize> {
+    let end = match end {
+        Some(end) => end,
+        None => {
+            // Gallop until the predicate holds
+            let mut width = 1;
+            while !pred(start + width) { width *= 2; }
+            start + width
+        }
+    };
+    if start > end || !pred(end) { return None; }
+    let (mut low, mut high) = (start, end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) { high = mid; } else { low = mid + 1; }
+    }
+    Some(low)
+}
+
+/// Steps spent in the columns of the target with a horizontal velocity
+fn x_steps(vx: isize, target: &Target) -> Option<Steps> {
+    // Mirror the target so that the probe always moves right
+    let (low, high) = if vx < 0 { (-target.x.1, -target.x.0) } else { target.x };
+    let v = vx.abs();
+    let x = |t: isize| { let t = t.min(v); t * v - t * (t - 1) / 2 };
+    if v == 0 {
+        return (low <= 0 && 0 <= high).then_some((1, None));
+    }
+    let start = first_step(1, Some(v), |t| x(t) >= low)?;
+    match first_step(1, Some(v), |t| x(t) > high).map(|t| t - 1) {
+        Some(end) if end < start => None,
+        end => Some((start, end))
+    }
+}
+
+/// Steps spent in the rows of the target with a vertical velocity
+fn y_steps(vy: isize, target: &Target) -> Vec<Steps> {
+    let (low, high) = target.y;
+    let y = |t: isize| t * vy - t * (t - 1) / 2;
+    let mut steps = Vec::new();
+    // Going up, from the first step to the apex
+    if vy >= 1 {
+        if let Some(start) = first_step(1, Some(vy), |t| y(t) >= low) {
+            let end = first_step(1, Some(vy), |t| y(t) > high)
+                .map_or(vy, |t| t - 1);
+            if start <= end { steps.push((start, Some(end))); }
+        }
+    }
+    // Going down forever, right after the apex
+    let apex = vy.max(0) + 1;
+    let start = first_step(apex, None, |t| y(t) <= high).unwrap();
+    let end = first_step(apex, None, |t| y(t) < low).unwrap() - 1;
+    if start <= end { steps.push((start, Some(end))); }
+    steps
+}
+
+/// Do two ranges of steps overlap?
+fn overlap(a: Steps, b: Steps) -> bool {
+    let start = a.0.max(b.0);
+    match (a.1, b.1) {
+        (Some(x), Some(y)) => start <= x.min(y),
+        (Some(x), None) | (None, Some(x)) => start <= x,
+        (None, None) => true
+    }
+}
+
+/// Find all of the velocities hitting the target
+///
+/// # Return Value
+///
+/// Returns the velocities sorted by increasing `(vx, vy)`.
+///
+/// # Errors
+///
+/// Returns [`TrickShotError::Unbounded`] when infinitely many velocities hit
+/// the target.
+pub fn hits(target: &Target) -> Result<Vec<Coords>, TrickShotError> {
+    let (x0, x1) = target.x;
+    let (y0, y1) = target.y;
+    // The first step already moves by the velocity, so the target cannot
+    // be hit with a larger sideways speed
+    let columns = (x0.min(0)..=x1.max(0))
+        .filter_map(|vx| x_steps(vx, target).map(|s| (vx, s)))
+        .collect::<Vec<(isize, Steps)>>();
+    let stops = columns.iter().any(|(_, s)| s.1.is_none());
+    // Going up, the first step is already higher than `y1` above `top`,
+    // and going down, the step right after the launch height is lower
+    // than `y0`. Going down from the start, the first step is already
+    // lower than `y0`.
+    let mut top = y1.max(-y0 - 1);
+    if y0 <= 0 && 0 <= y1 {
+        if stops { return Err(TrickShotError::Unbounded); }
+        // Falling back to the launch height happens after the probe has
+        // stopped moving sideways, outside of the columns of the target
+        top = top.max(x0.abs()).max(x1.abs());
+    }
+    let rows = (y0.min(0)..=top)
+        .map(|vy| (vy, y_steps(vy, target)))
+        .filter(|(_, s)| !s.is_empty())
+        .collect::<Vec<(isize, Vec<Steps>)>>();
+    Ok(columns.iter()
+        .flat_map(|&(vx, xs)| rows.iter()
+            .filter(move |(_, ys)| ys.iter().any(|&s| overlap(xs, s)))
+            .map(move |&(vy, _)| (vx, vy)))
+        .collect::<Vec<Coords>>())
+}
+
+/// Find the highest point reached by a probe that hits the target
+///
+/// # Return Value
+///
+/// Returns the highest position reached, or `None` if nothing hits.
+///
+/// # Errors
+///
+/// Returns [`TrickShotError::Unbounded`] when infinitely many velocities hit
+/// the target.
+pub fn max_height(target: &Target) -> Result<Option<isize>, TrickShotError> {
+    Ok(hits(target)?.into_iter()
+        .map(|(_, vy)| if vy > 0 { vy * (vy + 1) / 2 } else { 0 })
+        .max())
+}
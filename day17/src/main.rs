@@ -2,12 +2,47 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day17;
+use day17::{solve_part_one, solve_part_two, hits, Target};
+use day17::render::{outcome_map, outcome_svg, trajectories_svg};
+
+/// Read all of the velocities given with `--trajectory <vx>,<vy>` options
+fn velocities_from_args() -> Result<Vec<(isize, isize)>, Box<dyn std::error::Error>> {
+    let args = std::env::args().collect::<Vec<String>>();
+    args.windows(2)
+        .filter(|w| w[0] == "--trajectory")
+        .map(|w| {
+            let (x, y) = w[1].split_once(',')
+                .ok_or("expected --trajectory <vx>,<vy>")?;
+            Ok((x.parse::<isize>()?, y.parse::<isize>()?))
+        })
+        .collect()
+}
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    let target = data.parse::<Target>()?;
+    // `--map ascii` or `--map svg` draws the outcome of every velocity
+    // around the ones that hit
+    let mut args = std::env::args().skip_while(|a| a != "--map");
+    if args.next().is_some() {
+        let top = hits(&target)?.iter().map(|v| v.1).max().unwrap_or(0) + 2;
+        let vx = target.x.0.min(0) - 1..=target.x.1.max(0) + 1;
+        let vy = target.y.0.min(0) - 1..=top;
+        match args.next().as_deref() {
+            Some("ascii") => println!("{}", outcome_map(&target, vx, vy)),
+            Some("svg") => println!("{}", outcome_svg(&target, vx, vy)),
+            _ => return Err("expected --map ascii or --map svg".into())
+        }
+        return Ok(());
+    }
+    // `--trajectory <vx>,<vy>`, as many times as wanted, draws those shots
+    let velocities = velocities_from_args()?;
+    if !velocities.is_empty() {
+        println!("{}", trajectories_svg(&target, &velocities));
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -18,7 +53,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod test {
     use super::*;
     use common::test;
+    use day17::{big_shot, max_height, trajectory, Shot, TrickShotError};
+
+    /// Brute force all of the velocities of a window with the simulation
+    fn simulated_hits(target: &Target, window: isize) -> Vec<(isize, isize)> {
+        (-window..=window)
+            .flat_map(|vx| (-window..=window).map(move |vy| (vx, vy)))
+            .filter(|&v| big_shot(v, target) == Shot::Hit)
+            .collect::<Vec<(isize, isize)>>()
+    }
 
     test!(day17_01_example1, 1, 45, "target area: x=20..30, y=-10..-5");
     test!(day17_02_example1, 2, 112, "target area: x=20..30, y=-10..-5");
+
+    #[test]
+    fn day17_any_quadrant() {
+        let target = "target area: x=20..30, y=-10..-5".parse::<Target>().unwrap();
+        let mirrored = Target::new((-30, -20), (-10, -5));
+        let flipped = |hits: Vec<(isize, isize)>| {
+            let mut hits = hits.into_iter().map(|(x, y)| (-x, y)).collect::<Vec<_>>();
+            hits.sort_unstable();
+            hits
+        };
+        assert_eq!(flipped(hits(&target).unwrap()), hits(&mirrored).unwrap());
+        assert_eq!(Some(45), max_height(&mirrored).unwrap());
+        for target in [Target::new((20, 30), (5, 10)), Target::new((-4, 6), (-10, -5)),
+            Target::new((-30, -20), (3, 12)), Target::new((-3, 3), (4, 9)),
+            Target::new((11, 13), (-2, 3))] {
+            assert_eq!(simulated_hits(&target, 40), hits(&target).unwrap());
+        }
+        assert_eq!(Err(TrickShotError::Unbounded), hits(&Target::new((4, 7), (-2, 3))));
+    }
+
+    #[test]
+    fn day17_trajectory() {
+        let target = "target area: x=20..30, y=-10..-5".parse::<Target>().unwrap();
+        let shot = trajectory((7, 2), &target);
+        assert_eq!(Shot::Hit, shot.outcome);
+        assert_eq!(vec![(0, 0), (7, 2), (13, 3), (18, 3), (22, 2), (25, 0),
+            (27, -3), (28, -7)], shot.points);
+        assert_eq!(Shot::Tunneled, big_shot((6, -1), &target));
+        assert_eq!(Shot::Overshot, big_shot((17, -4), &target));
+        assert_eq!(Shot::Undershot, big_shot((5, 4), &target));
+        assert_eq!(Shot::Overshot, big_shot((31, 0), &target));
+    }
+
+    #[test]
+    fn day17_render() {
+        let target = "target area: x=20..30, y=-10..-5".parse::<Target>().unwrap();
+        assert_eq!("UUHHOOOO\nUUHHOOOO\nUUHHOOOO\nUUHHHOOO",
+            outcome_map(&target, 4..=11, 1..=4));
+        let svg = outcome_svg(&target, 0..=30, -10..=10);
+        assert_eq!(31 * 21 + 4, svg.matches("<rect").count());
+        let svg = trajectories_svg(&target, &[(7, 2), (17, -4)]);
+        assert_eq!(2, svg.matches("<polyline").count());
+        assert!(svg.contains("stroke=\"green\"") && svg.contains("stroke=\"red\""));
+    }
 }
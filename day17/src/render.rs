@@ -0,0 +1,130 @@
+//! Module drawing shot outcomes and trajectories
+//!
+//! The outcome map shows the [Shot] of every velocity of a window, with
+//! `vx` growing to the right and `vy` growing upwards. Trajectories are
+//! drawn in the frame of the puzzle, with height growing upwards as well.
+
+use std::ops::RangeInclusive;
+
+use crate::target::{big_shot, trajectory, Coords, Shot, Target};
+
+/// Size of a single velocity cell of the SVG outcome map
+const CELL: isize = 8;
+
+/// Letter of an outcome on the ASCII map
+fn letter(shot: Shot) -> char {
+    match shot {
+        Shot::Hit => 'H',
+        Shot::Undershot => 'U',
+        Shot::Overshot => 'O',
+        Shot::Tunneled => 'T'
+    }
+}
+
+/// Colour of an outcome on the SVG map
+fn colour(shot: Shot) -> &'static str {
+    match shot {
+        Shot::Hit => "#2ca02c",
+        Shot::Undershot => "#1f77b4",
+        Shot::Overshot => "#d62728",
+        Shot::Tunneled => "#ff7f0e"
+    }
+}
+
+/// Render the outcome of every velocity of a window as text
+///
+/// Every line is a vertical velocity, from the highest to the lowest, and
+/// every column a horizontal velocity, from the lowest to the highest.
+/// Outcomes are written `H` for hits, `U` for undershots, `O` for
+/// overshots and `T` for tunneled shots.
+#[must_use]
+pub fn outcome_map(target: &Target, vx: RangeInclusive<isize>,
+    vy: RangeInclusive<isize>) -> String {
+    vy.rev()
+        .map(|y| vx.clone()
+            .map(|x| letter(big_shot((x, y), target)))
+            .collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Render the outcome of every velocity of a window as an SVG heat-map
+///
+/// The layout is the one of [`outcome_map`], with a coloured square for
+/// every velocity, and a legend of the colours below the map.
+#[must_use]
+pub fn outcome_svg(target: &Target, vx: RangeInclusive<isize>,
+    vy: RangeInclusive<isize>) -> String {
+    let width = (vx.end() - vx.start() + 1).max(0) * CELL;
+    let height = (vy.end() - vy.start() + 1).max(0) * CELL;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width.max(4 * 12 * CELL), height + 3 * CELL);
+    for (row, y) in vy.rev().enumerate() {
+        for (col, x) in vx.clone().enumerate() {
+            let shot = big_shot((x, y), target);
+            svg.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>{},{} {:?}</title></rect>\n",
+                col as isize * CELL, row as isize * CELL, CELL, CELL,
+                colour(shot), x, y, shot));
+        }
+    }
+    for (i, shot) in [Shot::Hit, Shot::Undershot, Shot::Overshot, Shot::Tunneled]
+        .into_iter().enumerate() {
+        let left = i as isize * 12 * CELL;
+        svg.push_str(&format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            left, height + CELL, CELL, CELL, colour(shot)));
+        svg.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"{}\">{:?}</text>\n",
+            left + 2 * CELL, height + 2 * CELL, CELL + 2, shot));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Draw the trajectories of some velocities over the target as SVG
+///
+/// The launch point and the target area are drawn along with every
+/// trajectory, each one being followed until its outcome is known (see
+/// [`trajectory`]). Hits are drawn in green and misses in red.
+#[must_use]
+pub fn trajectories_svg(target: &Target, velocities: &[Coords]) -> String {
+    let paths = velocities.iter()
+        .map(|&v| trajectory(v, target))
+        .collect::<Vec<_>>();
+    // Bounding box of everything, with the launch point and target
+    let points = paths.iter()
+        .flat_map(|p| p.points.iter().copied())
+        .chain([(0, 0), (target.x.0, target.y.0), (target.x.1, target.y.1)]);
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (0, 0, 0, 0);
+    for (x, y) in points {
+        min_x = min_x.min(x); max_x = max_x.max(x);
+        min_y = min_y.min(y); max_y = max_y.max(y);
+    }
+    // Flip heights so that up is up, and leave a margin all around
+    let margin = 1;
+    let sx = |x: isize| x - min_x + margin;
+    let sy = |y: isize| max_y - y + margin;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\">\n",
+        max_x - min_x + 2 * margin, max_y - min_y + 2 * margin);
+    svg.push_str(&format!(
+        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#cccccc\"/>\n",
+        sx(target.x.0), sy(target.y.1),
+        target.x.1 - target.x.0, target.y.1 - target.y.0));
+    svg.push_str(&format!(
+        "  <circle cx=\"{}\" cy=\"{}\" r=\"0.5\" fill=\"black\"/>\n", sx(0), sy(0)));
+    for (v, path) in velocities.iter().zip(&paths) {
+        let polyline = path.points.iter()
+            .map(|&(x, y)| format!("{},{}", sx(x), sy(y)))
+            .collect::<Vec<String>>()
+            .join(" ");
+        svg.push_str(&format!(
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.3\"><title>{},{} {:?}</title></polyline>\n",
+            polyline, if path.outcome == Shot::Hit { "green" } else { "red" },
+            v.0, v.1, path.outcome));
+    }
+    svg.push_str("</svg>");
+    svg
+}
@@ -0,0 +1,131 @@
+//! Module defining the [`Target`] area and the simulation of probe shots
+
+use regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref RE_TARGET: Regex = Regex::new(
+        r"^target area: x=(-?\d+)\.\.(-?\d+), y=(-?\d+)\.\.(-?\d+)$").unwrap();
+}
+
+/// A position or a velocity, as `(x, y)`
+pub type Coords = (isize, isize);
+
+/// Target area of the probe
+///
+/// Both ranges are inclusive, and always stored with their lowest bound
+/// first. The target can be anywhere around the launch point `(0, 0)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    /// Horizontal range of the target
+    pub x: (isize, isize),
+    /// Vertical range of the target
+    pub y: (isize, isize)
+}
+
+impl Target {
+    /// Build a target from its two ranges, in any order
+    #[must_use]
+    pub fn new(x: (isize, isize), y: (isize, isize)) -> Target {
+        Target {
+            x: (x.0.min(x.1), x.0.max(x.1)),
+            y: (y.0.min(y.1), y.0.max(y.1))
+        }
+    }
+
+    /// Is the position inside of the target?
+    #[must_use]
+    pub fn contains(&self, (x, y): Coords) -> bool {
+        self.x.0 <= x && x <= self.x.1 && self.y.0 <= y && y <= self.y.1
+    }
+
+    /// Is the horizontal position on the launch side of the target?
+    fn is_short(&self, x: isize) -> bool {
+        (x < self.x.0 && self.x.0 > 0) || (x > self.x.1 && self.x.1 < 0)
+    }
+}
+
+/// Error thrown when parsing an incorrect target area
+#[derive(Debug)]
+pub struct ParseTargetError { }
+impl std::fmt::Display for ParseTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error unparsing target area!!")
+    }
+}
+impl std::error::Error for ParseTargetError { }
+
+impl std::str::FromStr for Target {
+    type Err = ParseTargetError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let caps = RE_TARGET.captures(data.trim()).ok_or(ParseTargetError { })?;
+        let values = (1..5)
+            .map(|n| caps[n].parse::<isize>().map_err(|_| ParseTargetError { }))
+            .collect::<Result<Vec<isize>, ParseTargetError>>()?;
+        Ok(Target::new((values[0], values[1]), (values[2], values[3])))
+    }
+}
+
+/// Outcome of a shot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shot {
+    /// The probe stopped moving sideways before reaching the target
+    Undershot,
+    /// The probe went past the target sideways
+    Overshot,
+    /// The probe fell through the columns of the target without ever
+    /// being inside of it on a step
+    Tunneled, // quantum leap
+    /// The probe was inside of the target after a step
+    Hit
+}
+
+/// Full path of a probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trajectory {
+    /// All positions of the probe, from the launch point to the position
+    /// where the outcome is known
+    pub points: Vec<Coords>,
+    /// Outcome of the shot
+    pub outcome: Shot
+}
+
+/// Follow a probe until it hits the target, or can never hit it
+///
+/// The probe stops as soon as it is in the target. It is a miss once it
+/// is falling below the target, or once it has stopped moving sideways
+/// outside of the columns of the target.
+#[must_use]
+pub fn trajectory(velocity: Coords, target: &Target) -> Trajectory {
+    let (mut cx, mut cy): Coords = (0, 0);
+    let (mut vx, mut vy) = velocity;
+    let mut points = vec![(cx, cy)];
+    loop {
+        // Next step
+        cx += vx; cy += vy;
+        // Change velocity
+        vx -= vx.signum();
+        vy -= 1;
+        points.push((cx, cy));
+        // Detect ending
+        let inside_columns = target.x.0 <= cx && cx <= target.x.1;
+        let outcome = if target.contains((cx, cy)) {
+            Shot::Hit
+        } else if !(vy < 0 && cy < target.y.0 || vx == 0 && !inside_columns) {
+            continue;
+        } else if inside_columns {
+            Shot::Tunneled
+        } else if target.is_short(cx) {
+            Shot::Undershot
+        } else {
+            Shot::Overshot
+        };
+        return Trajectory { points, outcome };
+    }
+}
+
+/// Now is your chance to be a big shot
+#[must_use]
+pub fn big_shot(velocity: Coords, target: &Target) -> Shot {
+    trajectory(velocity, target).outcome
+}
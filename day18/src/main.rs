@@ -2,12 +2,53 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day18;
+use day18::{solve_part_one, solve_part_two, parse_numbers, top_pairs};
+
+/// Print every addition of part one, with all of its reduction steps
+fn print_trace(data: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let numbers = parse_numbers(data)?;
+    let Some((&first, rest)) = numbers.split_first() else { return Ok(()); };
+    let mut sum = first;
+    for &n in rest {
+        println!("  {}\n+ {}", sum, n);
+        let (res, events) = sum.add_with_trace(n);
+        for e in events {
+            println!("{}", e);
+        }
+        println!("= {}\n", res);
+        sum = res;
+    }
+    Ok(())
+}
+
+/// Print the `k` pairs with the largest sums
+fn print_top(data: &str, k: usize) -> Result<(), Box<dyn std::error::Error>> {
+    for p in top_pairs(&parse_numbers(data)?, k) {
+        println!("{} + {} = {} ({})", p.left, p.right, p.sum, p.magnitude);
+    }
+    Ok(())
+}
+
+/// Read the number of pairs from a `--top <k>` command line option
+fn top_from_args() -> Result<Option<usize>, Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip_while(|a| a != "--top");
+    match (args.next(), args.next()) {
+        (None, _) => Ok(None),
+        (Some(_), None) => Err("missing value for --top".into()),
+        (Some(_), Some(k)) => Ok(Some(k.parse::<usize>()?))
+    }
+}
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    if std::env::args().any(|a| a == "--trace") {
+        print_trace(&data)?;
+    }
+    if let Some(k) = top_from_args()? {
+        print_top(&data, k)?;
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -17,6 +58,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use day18::{max_pair, ParseSnailError, Reduction, Rules, Side, SnailNumber, SnailTree};
 
     macro_rules! test {
         ($fn:ident, $part:literal, $exp:literal, $data:literal) => {
@@ -41,4 +83,87 @@ mod test {
     test!(day18_01_example6, 1, 3488, "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]\n[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]\n[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]\n[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]\n[7,[5,[[3,8],[1,4]]]]\n[[2,[2,2]],[8,[8,1]]]\n[2,9]\n[1,[[[9,3],9],[[9,0],[0,7]]]]\n[[[5,[7,4]],7],1]\n[[[[4,2],2],6],[8,7]]");
     test!(day18_01_example7, 1, 4140, "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]\n[[[5,[2,8]],4],[5,[[9,9],0]]]\n[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]\n[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]\n[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]\n[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]\n[[[[5,4],[7,7]],8],[[8,3],8]]\n[[9,3],[[9,9],[6,[4,9]]]]\n[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]\n[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]");
     test!(day18_02_example1, 2, 3993, "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]\n[[[5,[2,8]],4],[5,[[9,9],0]]]\n[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]\n[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]\n[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]\n[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]\n[[[[5,4],[7,7]],8],[[8,3],8]]\n[[9,3],[[9,9],[6,[4,9]]]]\n[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]\n[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]");
+
+    #[test]
+    fn day18_snail_numbers() {
+        let n1 = "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]".parse::<SnailNumber>().unwrap();
+        let n2 = "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]".parse::<SnailNumber>().unwrap();
+        assert_eq!("[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]",
+            format!("{}", n1 + n2));
+        let t1 = "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]".parse::<SnailTree>().unwrap();
+        let t2 = "[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]".parse::<SnailTree>().unwrap();
+        assert_eq!(format!("{}", n1 + n2), format!("{}", t1 + t2));
+        assert_eq!(Err(ParseSnailError::TooDeep), "[[[[[[1,2],3],4],5],6]".parse::<SnailNumber>());
+        // Adding this one would need a sixth level
+        assert_eq!(Err(ParseSnailError::TooDeep), "[[[[[9,8],1],2],3],4]".parse::<SnailNumber>());
+        assert_eq!(5, "[[[[[9,8],1],2],3],4]".parse::<SnailTree>().unwrap().depth());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[,]".parse::<SnailNumber>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[1,2][3,4]".parse::<SnailNumber>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[1[2,3]]".parse::<SnailNumber>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[,]".parse::<SnailTree>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[1,2][3,4]".parse::<SnailTree>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[1[2,3]]".parse::<SnailTree>());
+        assert_eq!(Err(ParseSnailError::Unbalanced), "[[1,2],3".parse::<SnailTree>());
+        assert_eq!(Err(ParseSnailError::Unexpected('x')), "[x,3]".parse::<SnailTree>());
+    }
+
+    #[test]
+    fn day18_snail_tree_rules() {
+        let rules = Rules { explode_depth: 3, split_threshold: 8 };
+        let t = "[1,[2,[3,[4,5]]]]".parse::<SnailTree>().unwrap()
+            .add_with(&"[9,1]".parse::<SnailTree>().unwrap(), &rules);
+        assert_eq!("[[5,[0,5]],[[7,7],1]]", format!("{}", t));
+        assert_eq!(319, t.magnitude());
+        let rules = Rules { explode_depth: 6, split_threshold: 10 };
+        let mut t = "[[[[[[[1,2],3],4],5],6],7],[8,9]]".parse::<SnailTree>().unwrap();
+        assert_eq!(7, t.depth());
+        t.reduce_with(&rules);
+        assert_eq!("[[[[[[0,5],4],5],6],7],[8,9]]", format!("{}", t));
+        assert_eq!(3582, t.magnitude());
+    }
+
+    #[test]
+    fn day18_reduction_trace() {
+        use Side::{Left as L, Right as R};
+        let n1 = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<SnailNumber>().unwrap();
+        let n2 = "[1,1]".parse::<SnailNumber>().unwrap();
+        let (sum, events) = n1.add_with_trace(n2);
+        assert_eq!("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", format!("{}", sum));
+        assert_eq!(vec![
+            Reduction::Explode { left: 4, right: 3 },
+            Reduction::Explode { left: 8, right: 4 },
+            Reduction::Split { value: 15 },
+            Reduction::Split { value: 13 },
+            Reduction::Explode { left: 6, right: 7 }
+        ], events.iter().map(|e| e.action).collect::<Vec<Reduction>>());
+        assert_eq!(vec![L, L, L, L], events[0].path);
+        assert_eq!(vec![L, R, L], events[2].path);
+        assert_eq!("after explode [4,3] at LLLL:\t[[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+            format!("{}", events[0]));
+        assert_eq!(sum, events.last().unwrap().after);
+        let t1 = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse::<SnailTree>().unwrap();
+        let t2 = "[1,1]".parse::<SnailTree>().unwrap();
+        let (tsum, tevents) = t1.add_with_trace(&t2, &Rules::default());
+        assert_eq!(format!("{}", sum), format!("{}", tsum));
+        assert_eq!(events.len(), tevents.len());
+        for (e, t) in events.iter().zip(&tevents) {
+            assert_eq!((e.action, &e.path), (t.action, &t.path));
+            assert_eq!(format!("{}", e), format!("{}", t));
+        }
+    }
+
+    #[test]
+    fn day18_max_pair() {
+        let numbers = parse_numbers("[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]\n[[[5,[2,8]],4],[5,[[9,9],0]]]\n[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]\n[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]\n[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]\n[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]\n[[[[5,4],[7,7]],8],[[8,3],8]]\n[[9,3],[[9,9],[6,[4,9]]]]\n[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]\n[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]").unwrap();
+        let best = max_pair(&numbers).unwrap();
+        assert_eq!((8, 0, 3993), (best.left, best.right, best.magnitude));
+        assert_eq!("[[[[7,8],[6,6]],[[6,0],[7,7]]],[[[7,8],[8,8]],[[7,9],[0,6]]]]",
+            format!("{}", best.sum));
+        let top = top_pairs(&numbers, 5);
+        assert_eq!(5, top.len());
+        assert_eq!(best, top[0]);
+        assert!(top.windows(2).all(|w| w[0].magnitude >= w[1].magnitude));
+        assert_eq!(90, top_pairs(&numbers, 1000).len());
+        assert_eq!(None, max_pair(&numbers[..1]));
+    }
 }
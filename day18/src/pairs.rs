@@ -0,0 +1,77 @@
+//! Module searching for the pairs of numbers with the largest sums
+//!
+//! Addition of snailfish numbers is not commutative, so every ordered pair
+//! of distinct numbers is tried. All of the sums are independent, so they
+//! are spread across all cores with [rayon].
+
+use rayon::prelude::*;
+
+use crate::snailnumber::SnailNumber;
+
+/// Sum of an ordered pair of numbers from a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairSum {
+    /// Index of the number on the left of the addition
+    pub left: usize,
+    /// Index of the number on the right of the addition
+    pub right: usize,
+    /// The reduced sum
+    pub sum: SnailNumber,
+    /// Magnitude of the sum
+    pub magnitude: usize
+}
+
+impl PairSum {
+    fn new(numbers: &[SnailNumber], left: usize, right: usize) -> PairSum {
+        let sum = numbers[left] + numbers[right];
+        PairSum { left, right, sum, magnitude: sum.magnitude() }
+    }
+}
+
+/// Magnitudes of the sums of all ordered pairs, as `(magnitude, left, right)`
+fn magnitudes(numbers: &[SnailNumber])
+    -> impl ParallelIterator<Item = (usize, usize, usize)> + '_ {
+    let n = numbers.len();
+    (0..n).into_par_iter()
+        .flat_map_iter(move |i| (0..n)
+            .filter(move |&j| j != i)
+            .map(move |j| ((numbers[i] + numbers[j]).magnitude(), i, j)))
+}
+
+/// Order pairs by decreasing magnitude, then by increasing indices
+fn rank(a: &(usize, usize, usize), b: &(usize, usize, usize)) -> std::cmp::Ordering {
+    b.0.cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2)))
+}
+
+/// Find the ordered pair of distinct numbers with the largest sum
+///
+/// # Return Value
+///
+/// Returns the [`PairSum`] with the largest magnitude. When several pairs
+/// share it, the one with the smallest indices is returned. Returns `None`
+/// when there are less than two numbers.
+#[must_use]
+pub fn max_pair(numbers: &[SnailNumber]) -> Option<PairSum> {
+    magnitudes(numbers)
+        .min_by(rank)
+        .map(|(_, i, j)| PairSum::new(numbers, i, j))
+}
+
+/// Find the `k` ordered pairs of distinct numbers with the largest sums
+///
+/// # Return Value
+///
+/// Returns at most `k` [`PairSum`]s, from the largest magnitude to the
+/// smallest, using the same order as [`max_pair`] for ties.
+#[must_use]
+pub fn top_pairs(numbers: &[SnailNumber], k: usize) -> Vec<PairSum> {
+    let mut all = magnitudes(numbers).collect::<Vec<(usize, usize, usize)>>();
+    if k < all.len() {
+        all.select_nth_unstable_by(k, rank);
+        all.truncate(k);
+    }
+    all.sort_unstable_by(rank);
+    all.into_iter()
+        .map(|(_, i, j)| PairSum::new(numbers, i, j))
+        .collect::<Vec<PairSum>>()
+}
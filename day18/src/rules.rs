@@ -0,0 +1,18 @@
+//! Module defining the [Rules] of the reduction of snailfish numbers
+
+/// Rules followed when reducing a snailfish number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// A pair of regular numbers nested inside that many pairs explodes
+    pub explode_depth: usize,
+    /// A regular number that is at least that large splits
+    pub split_threshold: usize
+}
+
+impl Default for Rules {
+    /// The rules of the puzzle: pairs nested inside four pairs explode,
+    /// and numbers of 10 or more split.
+    fn default() -> Self {
+        Rules { explode_depth: 4, split_threshold: 10 }
+    }
+}
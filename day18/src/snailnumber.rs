@@ -0,0 +1,360 @@
+//! Module defining the flat [`SnailNumber`]
+
+use crate::ParseSnailError;
+use crate::trace::{Reduction, Side, TraceEvent};
+
+fn bit_height(a: usize) -> usize {
+    let mut n = 6;
+    while n > 0 && a & (1<<n) == 0 { n -= 1; }
+    n
+}
+
+fn is_left(a: usize, b: usize) -> bool {
+    // Which is the smallest
+    let b_height_a = bit_height(a);
+    let b_height_b = bit_height(b);
+    match (b_height_a < b_height_b, b_height_a > b_height_b) {
+        (false, false) => a < b,
+        (false, true) => {
+            let mut new_b = b;
+            for _ in b_height_b..b_height_a { new_b *= 2; }
+            a < new_b
+        },
+        (true, false) => {
+            let mut new_a = a;
+            for _ in b_height_a..b_height_b { new_a *= 2; }
+            new_a < b
+        },
+        _ => unreachable!()
+    }
+}
+
+/// A single slot of a [`SnailNumber`]
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum SnailItem {
+    Regular(usize),
+    Snail,
+    None
+}
+
+/// Snailfish number stored as a flat, heap-indexed array
+///
+/// This representation is cheap to copy, but it can only hold pairs
+/// nested inside at most 4 other pairs, which is as deep as the sum of two
+/// reduced numbers goes with the rules of the puzzle. Only numbers that
+/// could be reduced already, with pairs nested inside at most 3 others,
+/// are read, so that any two of them can be added. Use a [`SnailTree`]
+/// for deeper numbers or other [`Rules`].
+///
+/// [`SnailTree`]: crate::snailtree::SnailTree
+/// [`Rules`]: crate::rules::Rules
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct SnailNumber {
+    matrix: [SnailItem; 64]
+}
+
+impl std::ops::Add for SnailNumber {
+    type Output = Self;
+
+    fn add(self, data: Self) -> Self {
+        let mut res = self.concat(data);
+        res.reduce();
+        res
+    }
+}
+
+impl SnailNumber {
+    /// Build the pair of two numbers, without reducing it
+    fn concat(self, data: Self) -> Self {
+        // When you add, it's like shoving the two tables into one
+        let lefttab = self.matrix;
+        let righttab = data.matrix;
+        let mut endtab = [SnailItem::None; 64];
+        endtab[2] = SnailItem::Snail;
+        endtab[3] = SnailItem::Snail;
+        // Insert left elements
+        for (pos, &item) in lefttab.iter().enumerate().skip(2) {
+            if item == SnailItem::None { continue; }
+            // How deep is this element ?
+            let mut n = 6;
+            while pos & (1 << n) == 0 { n -= 1; }
+            // Trim the leading one, replace with 10
+            let npos = (2 << n) | pos ^ (1 << n);
+            endtab[npos] = item;
+        }
+        // Insert right elements
+        for (pos, &item) in righttab.iter().enumerate().skip(2) {
+            if item == SnailItem::None { continue; }
+            // How deep is this element ?
+            let mut n = 6;
+            while pos & (1 << n) == 0 { n -= 1; }
+            // Trim the leading 1, replace with 11
+            let npos = (3 << n) | pos ^ (1 << n);
+            endtab[npos] = item;
+        }
+        SnailNumber { matrix: endtab }
+    }
+
+    /// Add two numbers, recording every step of the reduction
+    ///
+    /// # Return Value
+    ///
+    /// Returns the sum, and all of the [`TraceEvent`]s of its reduction.
+    /// The first event starts from the unreduced pair of both numbers.
+    #[must_use]
+    pub fn add_with_trace(self, data: Self)
+        -> (SnailNumber, Vec<TraceEvent<SnailNumber>>) {
+        let mut res = self.concat(data);
+        let events = res.reduce_with_trace();
+        (res, events)
+    }
+
+    /// Reduce the number, recording every step of the reduction
+    pub fn reduce_with_trace(&mut self) -> Vec<TraceEvent<SnailNumber>> {
+        let mut events = Vec::new();
+        loop {
+            let before = *self;
+            let Some((action, pos)) = self.reduce_step() else { break; };
+            // The path is written in the bits that follow the leading one
+            let path = (0..bit_height(pos)).rev()
+                .map(|b| if pos & (1 << b) == 0 { Side::Left } else { Side::Right })
+                .collect::<Vec<Side>>();
+            events.push(TraceEvent { action, path, before, after: *self });
+        }
+        events
+    }
+}
+
+impl std::fmt::Display for SnailNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{},{}]", self.make_string(2), self.make_string(3))
+    }
+}
+
+impl SnailNumber {
+    // The coordinates trick I'm using is quite simple
+    // Taking the left node appends a 0 to the bit pattern,
+    // Taking the right node appends a 1 to the bit pattern.
+    // Since multiple bit patterns could end up on `0`,
+    // You just add a `1` to prepend all bit patterns.
+    // That way, "left node at depth 1" is "10", then left node
+    // of the left node at depth 2 is "100", and so on. That way,
+    // only lower layer items have "1" in their prefix (second half)
+    // of the storage matrix), and all others have "0" (first half of the
+    // matrix).
+    // Zero is, thus, never used.
+    fn set(&mut self, p: usize, s: SnailItem) {
+        self.matrix[p] = s;
+    }
+    fn get(&self, p: usize) -> SnailItem {
+        *self.matrix.get(p).unwrap()
+    }
+
+    fn reduce(&mut self) {
+        while self.reduce_step().is_some() { }
+    }
+
+    /// Apply the first action the reduction needs, if any
+    ///
+    /// # Return Value
+    ///
+    /// Returns the action, and the position of the pair that exploded or
+    /// of the number that was split. Returns `None` when the number is
+    /// already reduced.
+    fn reduce_step(&mut self) -> Option<(Reduction, usize)> {
+        // Do the first explosion
+        // In order to do so, iterate over all elements on layer 4,
+        // and find those that are SnailItem::Snail
+        for i in 16..32 {
+            if self.get(i) == SnailItem::Snail {
+                // Get the two numbers
+                let nleft = self.get_val(i*2);
+                // Look for a regular on the left, if any
+                if let Some(left_ipos) = self.get_num_left(i) {
+                    let a = self.get_val(left_ipos);
+                    self.set(left_ipos, SnailItem::Regular(a+nleft));
+                }
+                self.set(i*2, SnailItem::None);
+                let nright = self.get_val(i*2+1);
+                if let Some(right_ipos) = self.get_num_right(i) {
+                    let a = self.get_val(right_ipos);
+                    self.set(right_ipos, SnailItem::Regular(a+nright));
+                }
+                self.set(i*2+1, SnailItem::None);
+                self.set(i, SnailItem::Regular(0));
+                return Some((Reduction::Explode { left: nleft, right: nright }, i));
+            }
+        }
+        // Find all numbers that need reduction
+        let leftmost = self.matrix
+            .iter()
+            .enumerate()
+            .filter_map(|(p, i)| if let SnailItem::Regular(v) = i {
+                if *v > 9 { Some(p) } else { None }
+            } else { None })
+            .fold(None, |o, n| {
+                match o {
+                    None => Some(n),
+                    Some(v) => if is_left(n, v) { Some(n) } else { Some(v) }
+                }
+            });
+        let pos = leftmost?;
+        let val = self.get_val(pos);
+        // Insert
+        self.set(pos, SnailItem::Snail);
+        self.set(pos*2, SnailItem::Regular(val/2));
+        self.set(pos*2+1, SnailItem::Regular(
+            if val.is_multiple_of(2) { val / 2 } else { val / 2 + 1 }
+        ));
+        Some((Reduction::Split { value: val }, pos))
+    }
+
+    fn get_num_left(&self, p: usize) -> Option<usize> {
+        if p == 0b10000 { return None; } // Nope
+        // Operational order : look down on the left, then ask up
+        let mut c = p;
+        // Go up until there is a possible left (i.e c is odd)
+        while c.is_multiple_of(2) { c >>= 1; }
+        // Move left
+        c -= 1;
+        // Now delve until you find a number
+        while c < 64 {
+            if let SnailItem::Regular(_) = self.get(c) {
+                return Some(c);
+            }
+            c = c * 2 + 1;
+        }
+        None
+    }
+
+    fn get_num_right(&self, p: usize) -> Option<usize> {
+        if p == 0b11111 { return None; } // Nope
+        let mut c = p;
+        while c % 2 == 1 { c >>= 1; }
+        c += 1;
+        // Now delve and find a number
+        while c < 64 {
+            if let SnailItem::Regular(_) = self.get(c) {
+                return Some(c);
+            }
+            c *= 2;
+        }
+        None
+    }
+
+    fn get_val(&self, p: usize) -> usize {
+        if let SnailItem::Regular(v) = self.matrix[p] {
+            v
+        } else { panic!("NO VALUE AT {:b}", p) }
+    }
+
+    fn make_string(&self, p: usize) -> String {
+        match self.get(p) {
+            SnailItem::Regular(v) => format!("{}", v),
+            SnailItem::Snail =>
+                format!("[{},{}]", self.make_string(2*p), self.make_string(2*p+1)),
+            SnailItem::None => String::new()
+        }
+    }
+
+    /// Compute the magnitude of the number
+    ///
+    /// The magnitude of a pair is 3 times the magnitude of its left
+    /// element plus 2 times the magnitude of its right element, and the
+    /// magnitude of a regular number is that number.
+    #[must_use]
+    pub fn magnitude(&self) -> usize {
+        self.matrix.iter()
+            .enumerate()
+            .map(|(pos, val)| match val {
+                SnailItem::Regular(v) => {
+                    // One is right, zero is left
+                    let lead_zeros = pos.leading_zeros();
+                    let count_zeros = pos.count_zeros();
+                    let c_ones = pos.count_ones();
+                    // We have to deduce all leading zeros
+                    let ac_zeros = count_zeros - lead_zeros;
+                    //println!("{} located at {}=1 and {}=0", v, c_ones-1, ac_zeros);
+                    2_usize.pow(c_ones-1) * 3_usize.pow(ac_zeros)
+                        * v
+                },
+                _ => 0
+            })
+            .sum::<usize>()
+    }
+}
+
+impl Default for SnailNumber {
+    fn default() -> SnailNumber {
+        SnailNumber {
+            matrix: [SnailItem::None; 64]
+        }
+    }
+}
+
+impl std::iter::Sum for SnailNumber {
+    /// Add all of the numbers, from the first to the last
+    ///
+    /// The sum of no numbers at all is the empty [`SnailNumber::default`],
+    /// with a magnitude of 0.
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b).unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for SnailNumber {
+    type Err = ParseSnailError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let mut snail: [SnailItem; 64] = [SnailItem::None; 64];
+        let mut curpos = 1;
+        let mut closed = false;
+        let mut chars = data.chars().peekable();
+        while let Some(c) = chars.next() {
+            // Nothing may follow the outermost pair
+            if closed { return Err(ParseSnailError::Unbalanced); }
+            match c {
+                '[' => {
+                    // Enter next depth, left
+                    // This means the current position is the head
+                    // Of a snail number
+                    if snail[curpos] != SnailItem::None { return Err(ParseSnailError::Unbalanced); }
+                    snail[curpos] = SnailItem::Snail;
+                    curpos *= 2;
+                    // Leave room for the extra depth of a sum
+                    if curpos >= 32 { return Err(ParseSnailError::TooDeep); }
+                },
+                ']' => {
+                    // Close current depth, once both sides are there
+                    if curpos % 2 == 0 || snail[curpos] == SnailItem::None {
+                        return Err(ParseSnailError::Unbalanced);
+                    }
+                    curpos >>= 1;
+                    if curpos == 0 { return Err(ParseSnailError::Unbalanced); }
+                    closed = curpos == 1;
+                },
+                ',' => {
+                    // Next in depth, once the left side is there
+                    if curpos % 2 == 1 || snail[curpos] == SnailItem::None {
+                        return Err(ParseSnailError::Unbalanced);
+                    }
+                    curpos += 1;
+                },
+                n => {
+                    // This is hopefully a number, possibly of several digits
+                    if snail[curpos] != SnailItem::None { return Err(ParseSnailError::Unbalanced); }
+                    let mut digits = String::from(n);
+                    while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                        digits.push(d);
+                        chars.next();
+                    }
+                    snail[curpos] = SnailItem::Regular(digits.parse::<usize>()
+                        .map_err(|_| ParseSnailError::Unexpected(n))?);
+                }
+            }
+        }
+        if !closed {
+            return Err(ParseSnailError::Unbalanced);
+        }
+        Ok(SnailNumber { matrix: snail })
+    }
+}
@@ -0,0 +1,334 @@
+//! Module defining the arena-based [`SnailTree`]
+
+use crate::ParseSnailError;
+use crate::rules::Rules;
+use crate::trace::{Reduction, Side, TraceEvent};
+
+/// A node of a [`SnailTree`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    /// A regular number
+    Regular(usize),
+    /// A pair, with the arena indices of its left and right elements
+    Pair(usize, usize)
+}
+
+/// A regular number found while walking through a [`SnailTree`]
+#[derive(Debug, Clone, Copy)]
+struct Leaf {
+    /// Arena index of the number
+    node: usize,
+    /// Arena index of the pair holding the number, if any
+    parent: Option<usize>,
+    /// Number of pairs the number is nested in
+    depth: usize,
+    /// Number of times the path to the number goes left
+    lefts: usize
+}
+
+/// Snailfish number stored as a tree in an arena
+///
+/// Contrary to the flat [`SnailNumber`], this representation accepts any
+/// nesting depth and can be reduced with any [`Rules`]. Nothing in it is
+/// recursive, so very deep numbers do not overflow the stack either.
+///
+/// [`SnailNumber`]: crate::snailnumber::SnailNumber
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnailTree {
+    nodes: Vec<Node>,
+    root: usize
+}
+
+impl SnailTree {
+    /// Add two numbers and reduce the result with the given [`Rules`]
+    #[must_use]
+    pub fn add_with(&self, other: &SnailTree, rules: &Rules) -> SnailTree {
+        let mut res = self.concat(other);
+        res.reduce_with(rules);
+        res
+    }
+
+    /// Add two numbers, recording every step of the reduction
+    ///
+    /// # Return Value
+    ///
+    /// Returns the sum, and all of the [`TraceEvent`]s of its reduction.
+    /// The first event starts from the unreduced pair of both numbers.
+    #[must_use]
+    pub fn add_with_trace(&self, other: &SnailTree, rules: &Rules)
+        -> (SnailTree, Vec<TraceEvent<SnailTree>>) {
+        let mut res = self.concat(other);
+        let events = res.reduce_with_trace(rules);
+        (res, events)
+    }
+
+    /// Build the pair of two numbers, without reducing it
+    fn concat(&self, other: &SnailTree) -> SnailTree {
+        let offset = self.nodes.len();
+        let mut nodes = self.nodes.clone();
+        nodes.extend(other.nodes.iter().map(|&n| match n {
+            Node::Regular(v) => Node::Regular(v),
+            Node::Pair(l, r) => Node::Pair(l + offset, r + offset)
+        }));
+        nodes.push(Node::Pair(self.root, other.root + offset));
+        SnailTree { root: nodes.len() - 1, nodes }
+    }
+
+    /// Reduce the number with the given [`Rules`]
+    ///
+    /// Explosions of the leftmost pair deep enough are done first, and
+    /// the leftmost regular number large enough is split only when there
+    /// is nothing left to explode.
+    pub fn reduce_with(&mut self, rules: &Rules) {
+        while self.reduce_step(rules).is_some() { }
+        self.compact();
+    }
+
+    /// Reduce the number with the given [`Rules`], recording every step
+    pub fn reduce_with_trace(&mut self, rules: &Rules)
+        -> Vec<TraceEvent<SnailTree>> {
+        let mut events = Vec::new();
+        self.compact();
+        loop {
+            let before = self.clone();
+            let Some((action, node)) = self.reduce_step(rules) else { break; };
+            // Node indices are left untouched by a step, so the path can
+            // be looked for in the number from before it
+            let path = before.path_to(node);
+            self.compact();
+            events.push(TraceEvent { action, path, before, after: self.clone() });
+        }
+        events
+    }
+
+    /// Apply the first action the reduction needs, if any
+    ///
+    /// # Return Value
+    ///
+    /// Returns the action, and the arena index of the pair that exploded or
+    /// of the number that was split. Returns `None` when the number is
+    /// already reduced.
+    fn reduce_step(&mut self, rules: &Rules) -> Option<(Reduction, usize)> {
+        self.explode(rules).or_else(|| self.split(rules))
+    }
+
+    /// Explode the leftmost pair that needs it, if any
+    fn explode(&mut self, rules: &Rules) -> Option<(Reduction, usize)> {
+        let leaves = self.leaves();
+        // An exploding pair is made of two consecutive leaves that
+        // share the same parent
+        let k = leaves.windows(2).position(|w|
+            w[0].parent.is_some() && w[0].parent == w[1].parent &&
+            w[0].depth > rules.explode_depth)?;
+        let (left, right) = (self.value(leaves[k].node),
+            self.value(leaves[k + 1].node));
+        if k > 0 {
+            let n = leaves[k - 1].node;
+            self.nodes[n] = Node::Regular(self.value(n) + left);
+        }
+        if let Some(next) = leaves.get(k + 2) {
+            self.nodes[next.node] = Node::Regular(self.value(next.node) + right);
+        }
+        let pair = leaves[k].parent.unwrap();
+        self.nodes[pair] = Node::Regular(0);
+        Some((Reduction::Explode { left, right }, pair))
+    }
+
+    /// Split the leftmost regular number that needs it, if any
+    fn split(&mut self, rules: &Rules) -> Option<(Reduction, usize)> {
+        let leaf = self.leaves().into_iter()
+            .find(|l| self.value(l.node) >= rules.split_threshold)?;
+        let val = self.value(leaf.node);
+        self.nodes.push(Node::Regular(val / 2));
+        self.nodes.push(Node::Regular(val - val / 2));
+        let len = self.nodes.len();
+        self.nodes[leaf.node] = Node::Pair(len - 2, len - 1);
+        Some((Reduction::Split { value: val }, leaf.node))
+    }
+
+    /// Path from the root down to a node
+    fn path_to(&self, target: usize) -> Vec<Side> {
+        let mut stack = vec![(self.root, Vec::new())];
+        while let Some((node, path)) = stack.pop() {
+            if node == target { return path; }
+            if let Node::Pair(l, r) = self.nodes[node] {
+                let mut right = path.clone();
+                right.push(Side::Right);
+                let mut left = path;
+                left.push(Side::Left);
+                stack.push((r, right));
+                stack.push((l, left));
+            }
+        }
+        panic!("NO NODE AT {}", target)
+    }
+
+    /// Value of a regular number
+    fn value(&self, node: usize) -> usize {
+        match self.nodes[node] {
+            Node::Regular(v) => v,
+            Node::Pair(..) => panic!("NO VALUE AT {}", node)
+        }
+    }
+
+    /// All regular numbers, from left to right
+    fn leaves(&self) -> Vec<Leaf> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![Leaf { node: self.root, parent: None, depth: 0, lefts: 0 }];
+        while let Some(leaf) = stack.pop() {
+            match self.nodes[leaf.node] {
+                Node::Regular(_) => leaves.push(leaf),
+                Node::Pair(l, r) => {
+                    let depth = leaf.depth + 1;
+                    let parent = Some(leaf.node);
+                    // Right goes first so that left is popped first
+                    stack.push(Leaf { node: r, parent, depth, lefts: leaf.lefts });
+                    stack.push(Leaf { node: l, parent, depth, lefts: leaf.lefts + 1 });
+                }
+            }
+        }
+        leaves
+    }
+
+    /// Get rid of the nodes left unreachable by explosions
+    fn compact(&mut self) {
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        // Copy nodes in post-order so that children are always known
+        // by the time their pair is copied
+        let mut stack = vec![(self.root, false)];
+        let mut copied: Vec<usize> = Vec::new();
+        while let Some((node, visited)) = stack.pop() {
+            match (self.nodes[node], visited) {
+                (Node::Regular(v), _) => {
+                    nodes.push(Node::Regular(v));
+                    copied.push(nodes.len() - 1);
+                },
+                (Node::Pair(l, r), false) => {
+                    stack.push((node, true));
+                    stack.push((r, false));
+                    stack.push((l, false));
+                },
+                (Node::Pair(..), true) => {
+                    let r = copied.pop().unwrap();
+                    let l = copied.pop().unwrap();
+                    nodes.push(Node::Pair(l, r));
+                    copied.push(nodes.len() - 1);
+                }
+            }
+        }
+        self.root = nodes.len() - 1;
+        self.nodes = nodes;
+    }
+
+    /// Compute the magnitude of the number
+    ///
+    /// Every regular number counts 3 times for each time its path goes
+    /// left, and 2 times for each time it goes right.
+    #[must_use]
+    pub fn magnitude(&self) -> usize {
+        self.leaves().into_iter()
+            .map(|l| 3_usize.pow(l.lefts as u32)
+                * 2_usize.pow((l.depth - l.lefts) as u32)
+                * self.value(l.node))
+            .sum::<usize>()
+    }
+
+    /// Depth of the most nested pair, `0` for a single regular number
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.leaves().into_iter()
+            .map(|l| l.depth)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl std::ops::Add for SnailTree {
+    type Output = Self;
+
+    /// Add two numbers with the default [`Rules`]
+    fn add(self, data: Self) -> Self {
+        self.add_with(&data, &Rules::default())
+    }
+}
+
+impl std::iter::Sum for SnailTree {
+    /// Add all of the numbers, from the first to the last
+    ///
+    /// # Panics
+    ///
+    /// There is no empty [`SnailTree`], so summing no numbers panics.
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b).expect("Cannot sum no snailfish numbers")
+    }
+}
+
+impl std::fmt::Display for SnailTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Nodes still to be written, or pieces of punctuation
+        let mut stack: Vec<Result<usize, char>> = vec![Ok(self.root)];
+        while let Some(item) = stack.pop() {
+            match item {
+                Err(c) => write!(f, "{}", c)?,
+                Ok(node) => match self.nodes[node] {
+                    Node::Regular(v) => write!(f, "{}", v)?,
+                    Node::Pair(l, r) => {
+                        write!(f, "[")?;
+                        stack.extend([Err(']'), Ok(r), Err(','), Ok(l)]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SnailTree {
+    type Err = ParseSnailError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let mut nodes: Vec<Node> = Vec::new();
+        // Elements of the pairs that are still open, and whether their comma
+        // was seen
+        let mut open: Vec<(Vec<usize>, bool)> = Vec::new();
+        let mut root = None;
+        let mut chars = data.chars().peekable();
+        while let Some(c) = chars.next() {
+            let node = match c {
+                '[' => { open.push((Vec::new(), false)); continue; },
+                ',' => match open.last_mut() {
+                    Some((elements, comma)) if elements.len() == 1 && !*comma => {
+                        *comma = true;
+                        continue;
+                    },
+                    _ => return Err(ParseSnailError::Unbalanced)
+                },
+                ']' => match open.pop() {
+                    Some((elements, true)) if elements.len() == 2 =>
+                        Node::Pair(elements[0], elements[1]),
+                    _ => return Err(ParseSnailError::Unbalanced)
+                },
+                n => {
+                    let mut digits = String::from(n);
+                    while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                        digits.push(d);
+                        chars.next();
+                    }
+                    Node::Regular(digits.parse::<usize>()
+                        .map_err(|_| ParseSnailError::Unexpected(n))?)
+                }
+            };
+            nodes.push(node);
+            match open.last_mut() {
+                Some((elements, comma)) if elements.len() == usize::from(*comma) =>
+                    elements.push(nodes.len() - 1),
+                Some(_) => return Err(ParseSnailError::Unbalanced),
+                None if root.is_none() => root = Some(nodes.len() - 1),
+                None => return Err(ParseSnailError::Unbalanced)
+            }
+        }
+        match (root, open.is_empty()) {
+            (Some(root), true) => Ok(SnailTree { nodes, root }),
+            _ => Err(ParseSnailError::Unbalanced)
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Module defining the events recorded while tracing a reduction
+
+/// Direction taken when going down into a pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The left element of the pair
+    Left,
+    /// The right element of the pair
+    Right
+}
+
+/// A single action of the reduction of a snailfish number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    /// A pair of regular numbers exploded
+    Explode {
+        /// Left number of the pair
+        left: usize,
+        /// Right number of the pair
+        right: usize
+    },
+    /// A regular number was split into a pair
+    Split {
+        /// The number that was split
+        value: usize
+    }
+}
+
+/// A [Reduction] applied on a snailfish number of type `T`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent<T> {
+    /// What happened
+    pub action: Reduction,
+    /// Where it happened, from the outermost pair: the pair that exploded,
+    /// or the number that was split
+    pub path: Vec<Side>,
+    /// The whole number before the action
+    pub before: T,
+    /// The whole number after the action
+    pub after: T
+}
+
+impl std::fmt::Display for Reduction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reduction::Explode { left, right } =>
+                write!(f, "explode [{},{}]", left, right),
+            Reduction::Split { value } => write!(f, "split {}", value)
+        }
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for TraceEvent<T> {
+    /// Formatted as `after explode [4,3] at LLLL:` followed by the number
+    /// after the action, with `L` and `R` giving the path.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.iter()
+            .map(|s| match s { Side::Left => 'L', Side::Right => 'R' })
+            .collect::<String>();
+        write!(f, "after {} at {}:\t{}", self.action,
+            if path.is_empty() { "root" } else { &path }, self.after)
+    }
+}
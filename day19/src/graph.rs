@@ -0,0 +1,89 @@
+//! Module for the export of a [Registration] as an alignment graph
+//!
+//! Every scanner is a node, and every alignment is an edge going from the
+//! aligned scanner to the scanner it was aligned against. Edges carry the
+//! transformation between the frames of both scanners.
+
+use crate::registration::{Coords, Matrix, Registration};
+use crate::rotation::index_of;
+
+/// Export the alignment graph in the Graphviz DOT format
+///
+/// Scanners that could not be aligned are drawn dashed and in red, so
+/// they stand out.
+#[must_use]
+pub fn to_dot(registration: &Registration) -> String {
+    let mut dot = String::from("digraph alignment {\n");
+    for (i, t) in registration.transforms.iter().enumerate() {
+        match t {
+            Some(t) => dot.push_str(&format!(
+                "    s{} [label=\"scanner {}\\n{}\"];\n",
+                i, i, coords(t.translation))),
+            None => dot.push_str(&format!(
+                "    s{} [label=\"scanner {}\\nunaligned\", style=dashed, color=red];\n",
+                i, i))
+        }
+    }
+    for (i, alignment) in registration.alignments.iter().enumerate() {
+        let (Some(alignment), Some(t)) =
+            (alignment, registration.relative_transform(i)) else { continue; };
+        dot.push_str(&format!(
+            "    s{} -> s{} [label=\"{} beacons\\nrotation {}\\ntranslation {}\"];\n",
+            i, alignment.reference, alignment.overlap,
+            rotation(t.rotation), coords(t.translation)));
+    }
+    dot.push('}');
+    dot
+}
+
+/// Export the alignment graph as JSON
+///
+/// The document has a `scanners` array with the transformation of every
+/// scanner into the frame of scanner 0 (`null` if it is unaligned), and
+/// an `edges` array with the transformation of every aligned scanner into
+/// the frame of its reference.
+#[must_use]
+pub fn to_json(registration: &Registration) -> String {
+    let scanners = registration.transforms.iter()
+        .enumerate()
+        .map(|(i, t)| match t {
+            Some(t) => format!(
+                "{{\"id\":{},\"rotation\":{},\"translation\":{}}}",
+                i, matrix(t.rotation), vector(t.translation)),
+            None => format!(
+                "{{\"id\":{},\"rotation\":null,\"translation\":null}}", i)
+        })
+        .collect::<Vec<String>>();
+    let edges = registration.alignments.iter()
+        .enumerate()
+        .filter_map(|(i, alignment)| {
+            let alignment = (*alignment)?;
+            let t = registration.relative_transform(i)?;
+            Some(format!(
+                "{{\"from\":{},\"to\":{},\"overlap\":{},\"rotation\":{},\"translation\":{}}}",
+                i, alignment.reference, alignment.overlap,
+                matrix(t.rotation), vector(t.translation)))
+        })
+        .collect::<Vec<String>>();
+    format!("{{\"scanners\":[{}],\"edges\":[{}]}}",
+        scanners.join(","), edges.join(","))
+}
+
+fn coords(c: Coords) -> String {
+    format!("({},{},{})", c[0], c[1], c[2])
+}
+
+fn rotation(m: Matrix) -> String {
+    match index_of(m) {
+        Some(i) => format!("#{} {}", i, matrix(m)),
+        None => matrix(m)
+    }
+}
+
+fn vector(c: Coords) -> String {
+    format!("[{},{},{}]", c[0], c[1], c[2])
+}
+
+fn matrix(m: Matrix) -> String {
+    format!("[{},{},{}]", vector(m[0]), vector(m[1]), vector(m[2]))
+}
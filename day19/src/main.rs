@@ -2,12 +2,26 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day19;
+use day19::{solve_part_one, solve_part_two, parse_scanners};
+use day19::registration::{register, RegistrationConfig};
+use day19::graph::{to_dot, to_json};
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--graph dot` or `--graph json` exports the alignment graph instead
+    let mut args = std::env::args().skip_while(|a| a != "--graph");
+    if args.next().is_some() {
+        let registration = register(&parse_scanners(&data),
+            &RegistrationConfig::default());
+        match args.next().as_deref() {
+            Some("dot") => println!("{}", to_dot(&registration)),
+            Some("json") => println!("{}", to_json(&registration)),
+            _ => return Err("expected --graph dot or --graph json".into())
+        }
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -18,7 +32,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod test {
     use super::*;
     use common::test;
+    use day19::registration::{register, RegistrationConfig, Transform};
+    use day19::rotation::{ROTATIONS, IDENTITY, compose, inverse, index_of};
 
     test!(day19_01_example1, 1, 79, "--- scanner 0 ---\n404,-588,-901\n528,-643,409\n-838,591,734\n390,-675,-793\n-537,-823,-458\n-485,-357,347\n-345,-311,381\n-661,-816,-575\n-876,649,763\n-618,-824,-621\n553,345,-567\n474,580,667\n-447,-329,318\n-584,868,-557\n544,-627,-890\n564,392,-477\n455,729,728\n-892,524,684\n-689,845,-530\n423,-701,434\n7,-33,-71\n630,319,-379\n443,580,662\n-789,900,-551\n459,-707,401\n\n--- scanner 1 ---\n686,422,578\n605,423,415\n515,917,-361\n-336,658,858\n95,138,22\n-476,619,847\n-340,-569,-846\n567,-361,727\n-460,603,-452\n669,-402,600\n729,430,532\n-500,-761,534\n-322,571,750\n-466,-666,-811\n-429,-592,574\n-355,545,-477\n703,-491,-529\n-328,-685,520\n413,935,-424\n-391,539,-444\n586,-435,557\n-364,-763,-893\n807,-499,-711\n755,-354,-619\n553,889,-390\n\n--- scanner 2 ---\n649,640,665\n682,-795,504\n-784,533,-524\n-644,584,-595\n-588,-843,648\n-30,6,44\n-674,560,763\n500,723,-460\n609,671,-379\n-555,-800,653\n-675,-892,-343\n697,-426,-610\n578,704,681\n493,664,-388\n-671,-858,530\n-667,343,800\n571,-461,-707\n-138,-166,112\n-889,563,-600\n646,-828,498\n640,759,510\n-630,509,768\n-681,-892,-333\n673,-379,-804\n-742,-814,-386\n577,-820,562\n\n--- scanner 3 ---\n-589,542,597\n605,-692,669\n-500,565,-823\n-660,373,557\n-458,-679,-417\n-488,449,543\n-626,468,-788\n338,-750,-386\n528,-832,-391\n562,-778,733\n-938,-730,414\n543,643,-506\n-524,371,-870\n407,773,750\n-104,29,83\n378,-903,-323\n-778,-728,485\n426,699,580\n-438,-605,-362\n-469,-447,-387\n509,732,623\n647,635,-688\n-868,-804,481\n614,-800,639\n595,780,-596\n\n--- scanner 4 ---\n727,592,562\n-293,-554,779\n441,611,-461\n-714,465,-776\n-743,427,-804\n-660,-479,-426\n832,-632,460\n927,-485,-438\n408,393,-506\n466,436,-512\n110,16,151\n-258,-428,682\n-393,719,612\n-211,-452,876\n808,-476,-593\n-575,615,604\n-485,667,467\n-680,325,-822\n-627,-443,-432\n872,-547,-609\n833,512,582\n807,604,487\n839,-516,451\n891,-625,532\n-652,-548,-490\n30,-46,-14");
     test!(day19_02_example1, 2, 3621, "--- scanner 0 ---\n404,-588,-901\n528,-643,409\n-838,591,734\n390,-675,-793\n-537,-823,-458\n-485,-357,347\n-345,-311,381\n-661,-816,-575\n-876,649,763\n-618,-824,-621\n553,345,-567\n474,580,667\n-447,-329,318\n-584,868,-557\n544,-627,-890\n564,392,-477\n455,729,728\n-892,524,684\n-689,845,-530\n423,-701,434\n7,-33,-71\n630,319,-379\n443,580,662\n-789,900,-551\n459,-707,401\n\n--- scanner 1 ---\n686,422,578\n605,423,415\n515,917,-361\n-336,658,858\n95,138,22\n-476,619,847\n-340,-569,-846\n567,-361,727\n-460,603,-452\n669,-402,600\n729,430,532\n-500,-761,534\n-322,571,750\n-466,-666,-811\n-429,-592,574\n-355,545,-477\n703,-491,-529\n-328,-685,520\n413,935,-424\n-391,539,-444\n586,-435,557\n-364,-763,-893\n807,-499,-711\n755,-354,-619\n553,889,-390\n\n--- scanner 2 ---\n649,640,665\n682,-795,504\n-784,533,-524\n-644,584,-595\n-588,-843,648\n-30,6,44\n-674,560,763\n500,723,-460\n609,671,-379\n-555,-800,653\n-675,-892,-343\n697,-426,-610\n578,704,681\n493,664,-388\n-671,-858,530\n-667,343,800\n571,-461,-707\n-138,-166,112\n-889,563,-600\n646,-828,498\n640,759,510\n-630,509,768\n-681,-892,-333\n673,-379,-804\n-742,-814,-386\n577,-820,562\n\n--- scanner 3 ---\n-589,542,597\n605,-692,669\n-500,565,-823\n-660,373,557\n-458,-679,-417\n-488,449,543\n-626,468,-788\n338,-750,-386\n528,-832,-391\n562,-778,733\n-938,-730,414\n543,643,-506\n-524,371,-870\n407,773,750\n-104,29,83\n378,-903,-323\n-778,-728,485\n426,699,580\n-438,-605,-362\n-469,-447,-387\n509,732,623\n647,635,-688\n-868,-804,481\n614,-800,639\n595,780,-596\n\n--- scanner 4 ---\n727,592,562\n-293,-554,779\n441,611,-461\n-714,465,-776\n-743,427,-804\n-660,-479,-426\n832,-632,460\n927,-485,-438\n408,393,-506\n466,436,-512\n110,16,151\n-258,-428,682\n-393,719,612\n-211,-452,876\n808,-476,-593\n-575,615,604\n-485,667,467\n-680,325,-822\n-627,-443,-432\n872,-547,-609\n833,512,582\n807,604,487\n839,-516,451\n891,-625,532\n-652,-548,-490\n30,-46,-14");
+
+    #[test]
+    fn day19_registration() {
+        let scanners = vec![
+            vec![[0, 0, 0], [1, 2, 4], [7, 3, -5], [-2, 9, 11]],
+            vec![[20, 10, -30], [22, 9, -26], [23, 3, -35], [29, 12, -19], [0, 0, 1]],
+            vec![[100, 100, 100], [-3, 14, 15]]
+        ];
+        let config = RegistrationConfig { min_overlap: 4, fingerprint_threshold: None };
+        let registration = register(&scanners, &config);
+        assert_eq!(Some(Transform {
+            rotation: [[0, -1, 0], [1, 0, 0], [0, 0, 1]],
+            translation: [10, -20, 30]
+        }), registration.transforms[1]);
+        assert_eq!(vec![2], registration.unaligned());
+        assert_eq!(5, registration.beacons.len());
+        assert_eq!(registration.transforms[1], registration.relative_transform(1));
+        assert_eq!("{\"scanners\":[{\"id\":0,\"rotation\":[[1,0,0],[0,1,0],[0,0,1]],\"translation\":[0,0,0]},{\"id\":1,\"rotation\":[[0,-1,0],[1,0,0],[0,0,1]],\"translation\":[10,-20,30]},{\"id\":2,\"rotation\":null,\"translation\":null}],\"edges\":[{\"from\":1,\"to\":0,\"overlap\":4,\"rotation\":[[0,-1,0],[1,0,0],[0,0,1]],\"translation\":[10,-20,30]}]}",
+            to_json(&registration));
+        assert!(to_dot(&registration).contains("s1 -> s0"));
+    }
+
+    #[test]
+    fn day19_rotations() {
+        assert_eq!(IDENTITY, ROTATIONS[0]);
+        for a in ROTATIONS {
+            assert_eq!(IDENTITY, compose(a, inverse(a)));
+            for b in ROTATIONS {
+                assert!(index_of(compose(a, b)).is_some());
+            }
+        }
+        for (i, &a) in ROTATIONS.iter().enumerate() {
+            assert_eq!(Some(i), index_of(a));
+        }
+    }
 }
@@ -0,0 +1,291 @@
+//! Module for the registration of scanner point clouds
+//!
+//! Every scanner sees a cloud of beacons in its own frame of reference,
+//! with an unknown position and orientation. Registration finds, for each
+//! scanner, the rotation and translation that map its beacons into the
+//! frame of reference of scanner 0, using the beacons they have in common.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub use crate::rotation::{Coords, Matrix, IDENTITY};
+use crate::rotation::{ROTATIONS, rotate, compose, inverse};
+
+/// Transformation from the frame of a scanner to the frame of scanner 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    /// Rotation applied first
+    pub rotation: Matrix,
+    /// Translation applied after the rotation, which is also the position
+    /// of the scanner relative to scanner 0
+    pub translation: Coords
+}
+
+impl Transform {
+    /// The transformation of scanner 0 itself
+    pub const IDENTITY: Transform = Transform {
+        rotation: IDENTITY,
+        translation: [0, 0, 0]
+    };
+
+    /// Map a point from the scanner's frame into the frame of scanner 0
+    #[must_use]
+    pub fn apply(&self, p: Coords) -> Coords {
+        add(self.translation, rotate(self.rotation, p))
+    }
+
+    /// Compose two transformations
+    ///
+    /// The resulting [Transform] applies `other` first, then `self`.
+    #[must_use]
+    pub fn compose(&self, other: &Transform) -> Transform {
+        Transform {
+            rotation: compose(self.rotation, other.rotation),
+            translation: self.apply(other.translation)
+        }
+    }
+
+    /// Invert a transformation
+    #[must_use]
+    pub fn inverse(&self) -> Transform {
+        let rotation = inverse(self.rotation);
+        let back = rotate(rotation, self.translation);
+        Transform { rotation, translation: [-back[0], -back[1], -back[2]] }
+    }
+}
+
+/// Settings of the registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationConfig {
+    /// Number of beacons two scanners must have in common to be aligned
+    pub min_overlap: usize,
+    /// Minimum number of squared beacon distances two scanners must share
+    /// before trying to align them at all. `None` tries every pair of
+    /// scanners, which is slower but never misses an alignment.
+    pub fingerprint_threshold: Option<usize>
+}
+
+impl Default for RegistrationConfig {
+    /// The settings of the puzzle: 12 beacons in common, which means at
+    /// least `12 * 11 / 2 = 66` distances in common.
+    fn default() -> Self {
+        RegistrationConfig {
+            min_overlap: 12,
+            fingerprint_threshold: Some(66)
+        }
+    }
+}
+
+/// How a scanner was aligned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment {
+    /// The already aligned scanner this one was aligned against
+    pub reference: usize,
+    /// Number of beacons both scanners were found to have in common
+    pub overlap: usize
+}
+
+/// Outcome of a registration
+#[derive(Debug, Clone)]
+pub struct Registration {
+    /// Transformation of every scanner, in input order. Scanners that
+    /// could not be aligned have `None`.
+    pub transforms: Vec<Option<Transform>>,
+    /// How every scanner was aligned, in input order. Scanner 0 and the
+    /// scanners that could not be aligned have `None`.
+    pub alignments: Vec<Option<Alignment>>,
+    /// All of the beacons seen by aligned scanners, in the frame of
+    /// scanner 0
+    pub beacons: HashSet<Coords>
+}
+
+impl Registration {
+    /// Indices of the scanners that could not be aligned
+    #[must_use]
+    pub fn unaligned(&self) -> Vec<usize> {
+        self.transforms.iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_none())
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>()
+    }
+
+    /// Have all of the scanners been aligned?
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.transforms.iter().all(Option::is_some)
+    }
+
+    /// Transformation from the frame of a scanner to the frame of the
+    /// scanner it was aligned against
+    ///
+    /// Returns `None` for scanner 0 and the scanners that were not aligned.
+    #[must_use]
+    pub fn relative_transform(&self, scanner: usize) -> Option<Transform> {
+        let alignment = self.alignments.get(scanner).copied().flatten()?;
+        let reference = self.transforms[alignment.reference]?;
+        let own = self.transforms[scanner]?;
+        Some(reference.inverse().compose(&own))
+    }
+
+    /// Positions of the aligned scanners, relative to scanner 0
+    #[must_use]
+    pub fn positions(&self) -> Vec<Coords> {
+        self.transforms.iter()
+            .flatten()
+            .map(|t| t.translation)
+            .collect::<Vec<Coords>>()
+    }
+}
+
+/// Distance fingerprint of a point cloud
+///
+/// Squared distances between two beacons do not depend on the frame of
+/// reference, so they are indexed with every pair of beacons they come
+/// from. Pairs that share a distance are candidates for being the same two
+/// beacons seen from two scanners.
+struct Fingerprint {
+    pairs: HashMap<isize, Vec<(usize, usize)>>
+}
+
+impl Fingerprint {
+    fn new(beacons: &[Coords]) -> Fingerprint {
+        let mut pairs: HashMap<isize, Vec<(usize, usize)>> = HashMap::new();
+        for i in 1..beacons.len() {
+            for j in 0..i {
+                pairs.entry(distance(beacons[i], beacons[j]))
+                    .or_default()
+                    .push((i, j));
+            }
+        }
+        Fingerprint { pairs }
+    }
+
+    /// Count the distances shared with another fingerprint
+    fn shared(&self, other: &Fingerprint) -> usize {
+        self.pairs.iter()
+            .filter_map(|(d, p)| other.pairs.get(d).map(|q| p.len().min(q.len())))
+            .sum()
+    }
+}
+
+/// Align a set of scanner point clouds
+///
+/// # Arguments
+///
+///  - `scanners` : the beacons seen by every scanner, each in its own frame
+///    of reference. Scanner 0 is the reference of the registration.
+///  - `config` : the [`RegistrationConfig`] to use.
+///
+/// # Return Value
+///
+/// Returns a [Registration] with the [Transform] of every scanner that
+/// could be aligned, directly or through other scanners, with scanner 0.
+/// Scanners that cannot be reached that way are reported as unaligned.
+#[must_use]
+pub fn register(scanners: &[Vec<Coords>], config: &RegistrationConfig)
+    -> Registration {
+    let mut transforms: Vec<Option<Transform>> = vec![None; scanners.len()];
+    let mut alignments: Vec<Option<Alignment>> = vec![None; scanners.len()];
+    let mut beacons: HashSet<Coords> = HashSet::new();
+    if scanners.is_empty() {
+        return Registration { transforms, alignments, beacons };
+    }
+    let fingerprints = scanners.iter()
+        .map(|s| Fingerprint::new(s))
+        .collect::<Vec<Fingerprint>>();
+    // Beacons of the aligned scanners, already in the frame of scanner 0
+    let mut placed: Vec<Vec<Coords>> = vec![Vec::new(); scanners.len()];
+    transforms[0] = Some(Transform::IDENTITY);
+    placed[0] = scanners[0].clone();
+    // Every scanner is only used as a reference once, right after being
+    // aligned, which guarantees that the search ends
+    let mut queue: VecDeque<usize> = VecDeque::from([0]);
+    while let Some(reference) = queue.pop_front() {
+        for i in 0..scanners.len() {
+            if transforms[i].is_some() { continue; }
+            if let Some(threshold) = config.fingerprint_threshold {
+                if fingerprints[reference].shared(&fingerprints[i]) < threshold {
+                    continue;
+                }
+            }
+            if let Some((t, overlap)) = correlate(
+                &placed[reference], &fingerprints[reference],
+                &scanners[i], &fingerprints[i], config.min_overlap) {
+                transforms[i] = Some(t);
+                alignments[i] = Some(Alignment { reference, overlap });
+                placed[i] = scanners[i].iter()
+                    .map(|&p| t.apply(p))
+                    .collect::<Vec<Coords>>();
+                queue.push_back(i);
+            }
+        }
+    }
+    beacons.extend(placed.into_iter().flatten());
+    Registration { transforms, alignments, beacons }
+}
+
+/// Try and find the [Transform] mapping `floating` onto `anchored`
+///
+/// Every pair of beacons from both clouds that share the same distance
+/// gives a candidate rotation (see [`induce_rotation`]) and translation.
+/// That candidate is accepted, along with the number of beacons it
+/// matches, if it maps at least `min_overlap` beacons of `floating` onto
+/// beacons of `anchored`.
+fn correlate(anchored: &[Coords], anchored_fp: &Fingerprint,
+    floating: &[Coords], floating_fp: &Fingerprint, min_overlap: usize)
+    -> Option<(Transform, usize)> {
+    let known: HashSet<Coords> = anchored.iter().copied().collect();
+    let mut tried: HashSet<Transform> = HashSet::new();
+    for (d, anchored_pairs) in &anchored_fp.pairs {
+        let Some(floating_pairs) = floating_fp.pairs.get(d) else { continue; };
+        for &(a, b) in anchored_pairs {
+            for &(c, e) in floating_pairs {
+                // The pair of floating beacons can be matched either way
+                for (head, aux) in [(c, e), (e, c)] {
+                    let Some(rotation) = induce_rotation(
+                        sub(anchored[a], anchored[b]),
+                        sub(floating[head], floating[aux])) else { continue; };
+                    let t = Transform {
+                        rotation,
+                        translation: sub(anchored[a], rotate(rotation, floating[head]))
+                    };
+                    if !tried.insert(t) { continue; }
+                    let matches = floating.iter()
+                        .filter(|&&p| known.contains(&t.apply(p)))
+                        .count();
+                    if matches >= min_overlap {
+                        return Some((t, matches));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Deduce the rotation that maps the vector `b` onto the vector `a`
+///
+/// Only works when all three components of the vectors have distinct,
+/// non-zero absolute values, since otherwise several rotations are
+/// possible. Returns `None` in that case, or when none of the [ROTATIONS]
+/// maps the vectors onto one another.
+fn induce_rotation(a: Coords, b: Coords) -> Option<Matrix> {
+    let distinct = |v: Coords| v.iter().all(|&c| c != 0) &&
+        v[0].abs() != v[1].abs() && v[1].abs() != v[2].abs() &&
+        v[0].abs() != v[2].abs();
+    if !distinct(a) || !distinct(b) { return None; }
+    ROTATIONS.into_iter().find(|&rot| rotate(rot, b) == a)
+}
+
+fn add(a: Coords, b: Coords) -> Coords {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: Coords, b: Coords) -> Coords {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn distance(a: Coords, b: Coords) -> isize {
+    let d = sub(a, b);
+    d[0] * d[0] + d[1] * d[1] + d[2] * d[2]
+}
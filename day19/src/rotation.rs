@@ -0,0 +1,84 @@
+//! Module for the rotations of the scanners
+//!
+//! Scanners can be facing any of the 6 directions of the axes, and be
+//! rotated in any of 4 ways around that direction, for a total of 24
+//! orientations. Every orientation is a proper rotation matrix whose
+//! coefficients are all `-1`, `0` or `1`.
+
+/// Coordinates of a point, in any frame of reference
+pub type Coords = [isize; 3];
+/// Rotation matrix, applied on column vectors of [Coords]
+pub type Matrix = [[isize; 3]; 3];
+
+/// The identity [Matrix]
+pub const IDENTITY: Matrix = [
+    [1, 0, 0],
+    [0, 1, 0],
+    [0, 0, 1]
+];
+
+/// All 24 proper rotation matrices, starting with the [IDENTITY]
+///
+/// They are the signed permutation matrices with a determinant of `1`,
+/// ordered by permutation of the axes, then by signs.
+pub const ROTATIONS: [Matrix; 24] = [
+    [[ 1,  0,  0], [ 0,  1,  0], [ 0,  0,  1]],
+    [[ 1,  0,  0], [ 0, -1,  0], [ 0,  0, -1]],
+    [[-1,  0,  0], [ 0,  1,  0], [ 0,  0, -1]],
+    [[-1,  0,  0], [ 0, -1,  0], [ 0,  0,  1]],
+    [[ 1,  0,  0], [ 0,  0,  1], [ 0, -1,  0]],
+    [[ 1,  0,  0], [ 0,  0, -1], [ 0,  1,  0]],
+    [[-1,  0,  0], [ 0,  0,  1], [ 0,  1,  0]],
+    [[-1,  0,  0], [ 0,  0, -1], [ 0, -1,  0]],
+    [[ 0,  1,  0], [ 1,  0,  0], [ 0,  0, -1]],
+    [[ 0,  1,  0], [-1,  0,  0], [ 0,  0,  1]],
+    [[ 0, -1,  0], [ 1,  0,  0], [ 0,  0,  1]],
+    [[ 0, -1,  0], [-1,  0,  0], [ 0,  0, -1]],
+    [[ 0,  1,  0], [ 0,  0,  1], [ 1,  0,  0]],
+    [[ 0,  1,  0], [ 0,  0, -1], [-1,  0,  0]],
+    [[ 0, -1,  0], [ 0,  0,  1], [-1,  0,  0]],
+    [[ 0, -1,  0], [ 0,  0, -1], [ 1,  0,  0]],
+    [[ 0,  0,  1], [ 1,  0,  0], [ 0,  1,  0]],
+    [[ 0,  0,  1], [-1,  0,  0], [ 0, -1,  0]],
+    [[ 0,  0, -1], [ 1,  0,  0], [ 0, -1,  0]],
+    [[ 0,  0, -1], [-1,  0,  0], [ 0,  1,  0]],
+    [[ 0,  0,  1], [ 0,  1,  0], [-1,  0,  0]],
+    [[ 0,  0,  1], [ 0, -1,  0], [ 1,  0,  0]],
+    [[ 0,  0, -1], [ 0,  1,  0], [ 1,  0,  0]],
+    [[ 0,  0, -1], [ 0, -1,  0], [-1,  0,  0]]
+];
+
+/// Apply a rotation to a vector
+#[must_use]
+pub fn rotate(rot: Matrix, v: Coords) -> Coords {
+    [
+        v[0] * rot[0][0] + v[1] * rot[0][1] + v[2] * rot[0][2],
+        v[0] * rot[1][0] + v[1] * rot[1][1] + v[2] * rot[1][2],
+        v[0] * rot[2][0] + v[1] * rot[2][1] + v[2] * rot[2][2]
+    ]
+}
+
+/// Compose two rotations
+///
+/// The resulting [Matrix] applies `b` first, then `a`.
+#[must_use]
+pub fn compose(a: Matrix, b: Matrix) -> Matrix {
+    std::array::from_fn(|i| std::array::from_fn(|j|
+        (0..3).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+/// Invert a rotation
+///
+/// The inverse of a rotation matrix is its transpose.
+#[must_use]
+pub fn inverse(m: Matrix) -> Matrix {
+    std::array::from_fn(|i| std::array::from_fn(|j| m[j][i]))
+}
+
+/// Find the position of a rotation in [ROTATIONS]
+///
+/// Returns `None` if the [Matrix] is not one of the 24 rotations.
+#[must_use]
+pub fn index_of(m: Matrix) -> Option<usize> {
+    ROTATIONS.iter().position(|&r| r == m)
+}
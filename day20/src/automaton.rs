@@ -0,0 +1,328 @@
+//! Module defining a generic cellular [`Automaton`] on the infinite plane
+//!
+//! The next state of every pixel only depends on the pixels of its
+//! [`Neighbourhood`], through a [`Rule`]. Just like the enhancement of the
+//! puzzle, the image grows every step by the reach of the neighbourhood on
+//! every side, and all of the pixels further away share the same state,
+//! the exterior, which the rule updates as a pixel seeing only exterior.
+
+use crate::bitmap::Bitmap;
+
+/// Error thrown when a [`Rule`] does not fit its [`Neighbourhood`], or when
+/// reading an incorrect rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// A lookup table does not have one entry per neighbourhood state
+    TableLength { expected: usize, found: usize },
+    /// A lookup table would be too large for that many neighbours
+    TooManyNeighbours(usize),
+    /// A totalistic rule mentions more neighbours than there are
+    CountOutOfRange(usize),
+    /// The rule is not in the `B3/S23` notation
+    BadNotation
+}
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleError::TableLength { expected, found } =>
+                write!(f, "Lookup table has {} entries instead of {}", found, expected),
+            RuleError::TooManyNeighbours(n) =>
+                write!(f, "Too many neighbours ({}) for a lookup table", n),
+            RuleError::CountOutOfRange(n) =>
+                write!(f, "Rule mentions {} neighbours, more than there are", n),
+            RuleError::BadNotation => write!(f, "Expected a rule such as B3/S23")
+        }
+    }
+}
+impl std::error::Error for RuleError { }
+
+/// Largest neighbourhood a lookup table is allowed for
+const MAX_LOOKUP_NEIGHBOURS: usize = 24;
+
+/// Pixels seen by every pixel, as `(dx, dy)` offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbourhood {
+    offsets: Vec<(isize, isize)>
+}
+
+impl Neighbourhood {
+    /// The `(2r+1)x(2r+1)` square around the pixel, row by row from the top
+    /// left, as in the puzzle when `r` is 1
+    #[must_use]
+    pub fn moore(radius: usize) -> Neighbourhood {
+        let r = radius as isize;
+        Neighbourhood::custom((-r..=r).flat_map(|dy| (-r..=r).map(move |dx| (dx, dy))))
+    }
+
+    /// The pixels at most `r` steps away horizontally and vertically
+    #[must_use]
+    pub fn von_neumann(radius: usize) -> Neighbourhood {
+        let r = radius as isize;
+        Neighbourhood::custom((-r..=r)
+            .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+            .filter(|(dx, dy)| dx.abs() + dy.abs() <= r))
+    }
+
+    /// Any list of offsets, in the order a lookup table reads them
+    #[must_use]
+    pub fn custom<I: IntoIterator<Item = (isize, isize)>>(offsets: I) -> Neighbourhood {
+        Neighbourhood { offsets: offsets.into_iter().collect::<Vec<(isize, isize)>>() }
+    }
+
+    /// The offsets, in order
+    #[must_use]
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+
+    /// How far away a pixel can see, on either axis
+    #[must_use]
+    pub fn reach(&self) -> usize {
+        self.offsets.iter()
+            .map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of neighbours, not counting the pixel itself
+    fn outer_len(&self) -> usize {
+        self.offsets.iter().filter(|&&o| o != (0, 0)).count()
+    }
+}
+
+/// How the next state of a pixel follows from its neighbourhood
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// One entry for every state of the neighbourhood, the first offset
+    /// being the most significant bit of the index, like the enhancement
+    /// program of the puzzle
+    Lookup(Vec<bool>),
+    /// The state only depends on the pixel and on how many of its other
+    /// neighbours are lit: an unlit pixel is born with `birth[n]`, a lit
+    /// one survives with `survival[n]`, missing entries being `false`
+    OuterTotalistic { birth: Vec<bool>, survival: Vec<bool> }
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`
+    #[must_use]
+    pub fn life() -> Rule {
+        "B3/S23".parse::<Rule>().unwrap()
+    }
+
+    /// Next state of a pixel
+    ///
+    /// # Arguments
+    ///
+    ///  - `neighbourhood` : what the pixel sees.
+    ///  - `cell` : the state of the pixel at an offset.
+    fn apply<F: Fn((isize, isize)) -> bool>(&self, neighbourhood: &Neighbourhood, cell: F)
+        -> bool {
+        match self {
+            Rule::Lookup(table) => table[neighbourhood.offsets.iter()
+                .fold(0, |index, &o| index << 1 | usize::from(cell(o)))],
+            Rule::OuterTotalistic { birth, survival } => {
+                let lit = neighbourhood.offsets.iter()
+                    .filter(|&&o| o != (0, 0) && cell(o))
+                    .count();
+                let table = if cell((0, 0)) { survival } else { birth };
+                table.get(lit).copied().unwrap_or(false)
+            }
+        }
+    }
+
+    /// Check that the rule fits a neighbourhood
+    fn check(&self, neighbourhood: &Neighbourhood) -> Result<(), RuleError> {
+        match self {
+            Rule::Lookup(table) => {
+                let n = neighbourhood.offsets.len();
+                if n > MAX_LOOKUP_NEIGHBOURS {
+                    return Err(RuleError::TooManyNeighbours(n));
+                }
+                if table.len() != 1 << n {
+                    return Err(RuleError::TableLength { expected: 1 << n, found: table.len() });
+                }
+            },
+            Rule::OuterTotalistic { birth, survival } => {
+                let n = neighbourhood.outer_len();
+                for table in [birth, survival] {
+                    if let Some(count) = table.iter().rposition(|&on| on).filter(|&c| c > n) {
+                        return Err(RuleError::CountOutOfRange(count));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a Life-like rule in the `B3/S23` notation, case insensitive
+impl std::str::FromStr for Rule {
+    type Err = RuleError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let data = data.trim().to_ascii_uppercase();
+        let (birth, survival) = data.split_once('/').ok_or(RuleError::BadNotation)?;
+        let counts = |part: &str, prefix: char| -> Result<Vec<bool>, RuleError> {
+            let digits = part.strip_prefix(prefix).ok_or(RuleError::BadNotation)?;
+            let mut table = vec![false; 9];
+            for c in digits.chars() {
+                let n = c.to_digit(9).ok_or(RuleError::BadNotation)?;
+                table[n as usize] = true;
+            }
+            Ok(table)
+        };
+        Ok(Rule::OuterTotalistic { birth: counts(birth, 'B')?, survival: counts(survival, 'S')? })
+    }
+}
+
+/// Cellular automaton working on a dense [`Bitmap`]
+#[derive(Debug, Clone)]
+pub struct Automaton {
+    image: Bitmap,
+    exterior: bool,
+    neighbourhood: Neighbourhood,
+    rule: Rule,
+    /// Is the neighbourhood the 3x3 Moore one, as in the puzzle?
+    is_moore: bool
+}
+
+impl Automaton {
+    /// Build an automaton from its rule and its starting image, on an unlit
+    /// background
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RuleError`] if the rule does not fit the neighbourhood.
+    pub fn new(image: Bitmap, neighbourhood: Neighbourhood, rule: Rule)
+        -> Result<Automaton, RuleError> {
+        rule.check(&neighbourhood)?;
+        let is_moore = neighbourhood == Neighbourhood::moore(1);
+        Ok(Automaton { image, exterior: false, neighbourhood, rule, is_moore })
+    }
+
+    /// The current image, without the background
+    #[must_use]
+    pub fn image(&self) -> &Bitmap {
+        &self.image
+    }
+
+    /// The rule of the automaton
+    #[must_use]
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// The neighbourhood of every pixel
+    #[must_use]
+    pub fn neighbourhood(&self) -> &Neighbourhood {
+        &self.neighbourhood
+    }
+
+    /// Is the infinite background lit?
+    #[must_use]
+    pub fn exterior(&self) -> bool {
+        self.exterior
+    }
+
+    /// Number of lit pixels, or `None` if there are infinitely many
+    #[must_use]
+    pub fn count(&self) -> Option<usize> {
+        (!self.exterior).then(|| self.image.count())
+    }
+
+    /// The current image, with `margin` pixels of background all around
+    #[must_use]
+    pub fn framed(&self, margin: usize) -> Bitmap {
+        let mut frame = Bitmap::new(self.image.width() + 2 * margin,
+            self.image.height() + 2 * margin);
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let on = self.image.get(x as isize - margin as isize,
+                    y as isize - margin as isize, self.exterior);
+                frame.set(x, y, on);
+            }
+        }
+        frame
+    }
+
+    /// Run the automaton once
+    pub fn step(&mut self) {
+        self.image = match &self.rule {
+            Rule::Lookup(table) if self.is_moore =>
+                self.step_moore(table),
+            _ => self.step_any()
+        };
+        self.exterior = self.rule.apply(&self.neighbourhood, |_| self.exterior);
+    }
+
+    /// Next image, for any neighbourhood and rule
+    fn step_any(&self) -> Bitmap {
+        let reach = self.neighbourhood.reach();
+        let (width, height) = (self.image.width() + 2 * reach, self.image.height() + 2 * reach);
+        let mut next = Bitmap::new(width, height);
+        for y in 0..height {
+            // Pixel `(x, y)` of the new image is pixel `(x - reach, y - reach)`
+            // of the old one
+            let oy = y as isize - reach as isize;
+            for x in 0..width {
+                let ox = x as isize - reach as isize;
+                let on = self.rule.apply(&self.neighbourhood,
+                    |(dx, dy)| self.image.get(ox + dx, oy + dy, self.exterior));
+                if on { next.set(x, y, true); }
+            }
+        }
+        next
+    }
+
+    /// Next image, for a lookup table over the 3x3 Moore neighbourhood, as
+    /// in the puzzle
+    fn step_moore(&self, table: &[bool]) -> Bitmap {
+        let (width, height) = (self.image.width() + 2, self.image.height() + 2);
+        let mut next = Bitmap::new(width, height);
+        for y in 0..height {
+            // Pixel `(x, y)` of the new image is centered on pixel
+            // `(x - 1, y - 1)` of the old one. Slide a 3-pixel window along
+            // each of the three old rows it sees.
+            let oy = y as isize - 1;
+            let mut rows = [0_usize; 3];
+            for ox in -2..width as isize {
+                for (dy, row) in rows.iter_mut().enumerate() {
+                    let on = self.image.get(ox, oy + dy as isize - 1, self.exterior);
+                    *row = (*row << 1 | usize::from(on)) & 0b111;
+                }
+                // The window now spans old columns `ox - 2..=ox`
+                if ox < 0 { continue; }
+                let x = ox as usize;
+                let index = rows[0] << 6 | rows[1] << 3 | rows[2];
+                if table[index] { next.set(x, y, true); }
+            }
+        }
+        next
+    }
+
+    /// Run the automaton several times
+    ///
+    /// # Return Value
+    ///
+    /// Returns the number of lit pixels after every step, `None` standing
+    /// for infinitely many.
+    pub fn run(&mut self, steps: usize) -> Vec<Option<usize>> {
+        (0..steps)
+            .map(|_| { self.step(); self.count() })
+            .collect::<Vec<Option<usize>>>()
+    }
+}
+
+impl std::fmt::Display for Automaton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (width, height) = (self.image.width() as isize, self.image.height() as isize);
+        for y in -1..=height {
+            for x in -1..=width {
+                let on = self.image.get(x, y, self.exterior);
+                write!(f, "{}", if on { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
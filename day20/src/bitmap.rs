@@ -0,0 +1,63 @@
+//! Module defining a dense, bit-packed [`Bitmap`]
+
+/// Rectangle of pixels, one bit each
+///
+/// Every row is packed into whole `u64` words, least significant bit first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    /// Number of words used by a single row
+    row_words: usize,
+    bits: Vec<u64>
+}
+
+impl Bitmap {
+    /// A bitmap with all pixels off
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Bitmap {
+        let row_words = width.div_ceil(64);
+        Bitmap { width, height, row_words, bits: vec![0; row_words * height] }
+    }
+
+    /// Width of the bitmap
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the bitmap
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Is a pixel on?
+    ///
+    /// Pixels outside of the bitmap are `outside`.
+    #[must_use]
+    pub fn get(&self, x: isize, y: isize, outside: bool) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return outside;
+        }
+        let (x, y) = (x as usize, y as usize);
+        self.bits[y * self.row_words + x / 64] & (1 << (x % 64)) != 0
+    }
+
+    /// Turn a pixel on or off
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside of the bitmap.
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        assert!(x < self.width && y < self.height, "Pixel {:?} out of bounds", (x, y));
+        let word = &mut self.bits[y * self.row_words + x / 64];
+        if on { *word |= 1 << (x % 64); } else { *word &= !(1 << (x % 64)); }
+    }
+
+    /// Number of pixels that are on
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
@@ -0,0 +1,127 @@
+//! Module defining the [`DenseEnhancer`]
+
+use crate::automaton::{Automaton, Neighbourhood, Rule};
+use crate::bitmap::Bitmap;
+use crate::{ParseImageError, Program};
+
+/// Image enhancer working on a dense [`Bitmap`]
+///
+/// This is the [`Automaton`] with the 3x3 Moore neighbourhood, and the
+/// enhancement program as its lookup table.
+#[derive(Debug, Clone)]
+pub struct DenseEnhancer {
+    automaton: Automaton
+}
+
+impl DenseEnhancer {
+    /// Build an enhancer from its program and its starting image
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseImageError`] if the program does not have 512
+    /// entries.
+    pub fn new(prog: Program, image: Bitmap) -> Result<DenseEnhancer, ParseImageError> {
+        if prog.len() != 512 {
+            return Err(ParseImageError::ProgramLength(prog.len()));
+        }
+        let automaton = Automaton::new(image, Neighbourhood::moore(1), Rule::Lookup(prog))
+            .expect("a 512-entry table fits the 3x3 neighbourhood");
+        Ok(DenseEnhancer { automaton })
+    }
+
+    /// The current image, without the background
+    #[must_use]
+    pub fn image(&self) -> &Bitmap {
+        self.automaton.image()
+    }
+
+    /// The enhancement program
+    ///
+    /// # Panics
+    ///
+    /// Never panics, the rule of an enhancer is always a lookup table.
+    #[must_use]
+    pub fn program(&self) -> &Program {
+        match self.automaton.rule() {
+            Rule::Lookup(prog) => prog,
+            Rule::OuterTotalistic { .. } => unreachable!("Enhancers use lookup tables")
+        }
+    }
+
+    /// The underlying [`Automaton`]
+    #[must_use]
+    pub fn automaton(&self) -> &Automaton {
+        &self.automaton
+    }
+
+    /// The current image, with `margin` pixels of background all around
+    #[must_use]
+    pub fn framed(&self, margin: usize) -> Bitmap {
+        self.automaton.framed(margin)
+    }
+
+    /// Is the infinite background lit?
+    #[must_use]
+    pub fn exterior(&self) -> bool {
+        self.automaton.exterior()
+    }
+
+    /// Number of lit pixels, or `None` if there are infinitely many
+    #[must_use]
+    pub fn count(&self) -> Option<usize> {
+        self.automaton.count()
+    }
+
+    /// Enhance the image once
+    pub fn step(&mut self) {
+        self.automaton.step();
+    }
+
+    /// Enhance the image several times
+    ///
+    /// # Return Value
+    ///
+    /// Returns the number of lit pixels after every step, `None` standing
+    /// for infinitely many.
+    pub fn run(&mut self, steps: usize) -> Vec<Option<usize>> {
+        self.automaton.run(steps)
+    }
+}
+
+impl std::fmt::Display for DenseEnhancer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.automaton)
+    }
+}
+
+impl std::str::FromStr for DenseEnhancer {
+    type Err = ParseImageError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let (prog, image) = data.trim().split_once("\n\n")
+            .ok_or(ParseImageError::MissingImage)?;
+        let prog = prog.chars()
+            .map(pixel)
+            .collect::<Result<Program, ParseImageError>>()?;
+        let rows = image.split('\n')
+            .map(|l| l.chars().map(pixel).collect::<Result<Vec<bool>, _>>())
+            .collect::<Result<Vec<Vec<bool>>, ParseImageError>>()?;
+        let width = rows.first().map_or(0, Vec::len);
+        let mut bitmap = Bitmap::new(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != width { return Err(ParseImageError::RaggedRows); }
+            for (x, &on) in row.iter().enumerate() {
+                bitmap.set(x, y, on);
+            }
+        }
+        DenseEnhancer::new(prog, bitmap)
+    }
+}
+
+/// Read a `#` or `.` pixel
+fn pixel(c: char) -> Result<bool, ParseImageError> {
+    match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        c => Err(ParseImageError::UnknownPixel(c))
+    }
+}
@@ -0,0 +1,281 @@
+//! Module reading and writing images as files
+//!
+//! Images are written in the Netpbm formats, PBM (`P4`) and PGM (`P5`), and
+//! also as PNG when the `png` feature is enabled. Lit pixels are black, as
+//! `1` means black in PBM files. Both the plain (`P1`, `P2`) and the binary
+//! Netpbm formats can be read back.
+
+use std::path::{Path, PathBuf};
+
+use crate::bitmap::Bitmap;
+use crate::dense::DenseEnhancer;
+
+/// Error thrown when reading an incorrect image file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageFormatError {
+    /// The file is not in one of the supported formats
+    UnknownFormat,
+    /// The header is incorrect
+    BadHeader,
+    /// There are less pixels than the header says
+    Truncated,
+    /// The PNG decoder failed
+    Png(String)
+}
+impl std::fmt::Display for ImageFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageFormatError::UnknownFormat => write!(f, "Unknown image format"),
+            ImageFormatError::BadHeader => write!(f, "Incorrect image header"),
+            ImageFormatError::Truncated => write!(f, "Image data is truncated"),
+            ImageFormatError::Png(e) => write!(f, "PNG error: {}", e)
+        }
+    }
+}
+impl std::error::Error for ImageFormatError { }
+
+/// Image file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary Netpbm bitmap
+    Pbm,
+    /// Binary Netpbm greymap
+    Pgm,
+    /// Portable Network Graphics, with the `png` feature
+    #[cfg(feature = "png")]
+    Png
+}
+
+impl ImageFormat {
+    /// Usual file extension
+    #[must_use]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Pbm => "pbm",
+            ImageFormat::Pgm => "pgm",
+            #[cfg(feature = "png")]
+            ImageFormat::Png => "png"
+        }
+    }
+
+    /// Encode an image
+    #[must_use]
+    pub fn encode(&self, image: &Bitmap) -> Vec<u8> {
+        match self {
+            ImageFormat::Pbm => to_pbm(image),
+            ImageFormat::Pgm => to_pgm(image),
+            #[cfg(feature = "png")]
+            ImageFormat::Png => to_png(image)
+        }
+    }
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = ImageFormatError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        match data {
+            "pbm" => Ok(ImageFormat::Pbm),
+            "pgm" => Ok(ImageFormat::Pgm),
+            #[cfg(feature = "png")]
+            "png" => Ok(ImageFormat::Png),
+            _ => Err(ImageFormatError::UnknownFormat)
+        }
+    }
+}
+
+/// Encode an image as a binary PBM (`P4`) file
+#[must_use]
+pub fn to_pbm(image: &Bitmap) -> Vec<u8> {
+    let mut data = format!("P4\n{} {}\n", image.width(), image.height()).into_bytes();
+    for y in 0..image.height() as isize {
+        // Rows are padded to whole bytes, most significant bit first
+        for x in (0..image.width() as isize).step_by(8) {
+            data.push((0..8).fold(0, |acc, i|
+                acc | u8::from(image.get(x + i, y, false)) << (7 - i)));
+        }
+    }
+    data
+}
+
+/// Encode an image as a binary PGM (`P5`) file, with 255 grey levels
+#[must_use]
+pub fn to_pgm(image: &Bitmap) -> Vec<u8> {
+    let mut data = format!("P5\n{} {}\n255\n", image.width(), image.height()).into_bytes();
+    for y in 0..image.height() as isize {
+        for x in 0..image.width() as isize {
+            data.push(if image.get(x, y, false) { 0 } else { 255 });
+        }
+    }
+    data
+}
+
+/// Encode an image as a greyscale PNG file
+///
+/// # Panics
+///
+/// Panics if the encoder fails, which it only does when writing fails,
+/// and it only writes into memory.
+#[cfg(feature = "png")]
+#[must_use]
+pub fn to_png(image: &Bitmap) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data,
+        image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let pixels = to_pgm(image);
+    let header = pixels.len() - image.width() * image.height();
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels[header..]).unwrap();
+    writer.finish().unwrap();
+    data
+}
+
+/// Read the next number of a Netpbm header, skipping comments
+fn header_number(data: &[u8], pos: &mut usize) -> Result<usize, ImageFormatError> {
+    loop {
+        match data.get(*pos) {
+            Some(b'#') => {
+                while data.get(*pos).is_some_and(|&c| c != b'\n') { *pos += 1; }
+            },
+            Some(c) if c.is_ascii_whitespace() => *pos += 1,
+            Some(c) if c.is_ascii_digit() => break,
+            _ => return Err(ImageFormatError::BadHeader)
+        }
+    }
+    let start = *pos;
+    while data.get(*pos).is_some_and(u8::is_ascii_digit) { *pos += 1; }
+    std::str::from_utf8(&data[start..*pos]).ok()
+        .and_then(|n| n.parse::<usize>().ok())
+        .ok_or(ImageFormatError::BadHeader)
+}
+
+/// Read a Netpbm bitmap or greymap, plain or binary
+///
+/// Grey pixels darker than half of the maximum value are lit.
+///
+/// # Errors
+///
+/// Returns an [`ImageFormatError`] if the file is incorrect.
+pub fn from_netpbm(data: &[u8]) -> Result<Bitmap, ImageFormatError> {
+    let magic = data.get(..2).ok_or(ImageFormatError::UnknownFormat)?;
+    let mut pos = 2;
+    let width = header_number(data, &mut pos)?;
+    let height = header_number(data, &mut pos)?;
+    let maxval = match magic {
+        b"P2" | b"P5" => header_number(data, &mut pos)?,
+        b"P1" | b"P4" => 1,
+        _ => return Err(ImageFormatError::UnknownFormat)
+    };
+    let mut pixels: Vec<bool> = Vec::with_capacity(width * height);
+    match magic {
+        b"P1" => pixels.extend(data[pos..].iter()
+            .filter(|c| matches!(c, b'0' | b'1'))
+            .map(|&c| c == b'1')),
+        b"P2" => {
+            while pixels.len() < width * height {
+                pixels.push(header_number(data, &mut pos)
+                    .map_err(|_| ImageFormatError::Truncated)? * 2 < maxval);
+            }
+        },
+        b"P4" => {
+            // A single whitespace separates the header from the pixels
+            let row_bytes = width.div_ceil(8);
+            let body = data.get(pos + 1..).ok_or(ImageFormatError::Truncated)?;
+            for row in body.chunks(row_bytes).take(height) {
+                pixels.extend((0..width)
+                    .map(|x| row.get(x / 8).is_some_and(|b| b & (0x80 >> (x % 8)) != 0)));
+            }
+        },
+        _ => {
+            let wide = maxval > 255;
+            let body = data.get(pos + 1..).ok_or(ImageFormatError::Truncated)?;
+            pixels.extend(body.chunks(if wide { 2 } else { 1 })
+                .take(width * height)
+                .map(|v| v.iter().fold(0, |acc, &b| acc << 8 | b as usize) * 2 < maxval));
+        }
+    }
+    if pixels.len() < width * height {
+        return Err(ImageFormatError::Truncated);
+    }
+    let mut image = Bitmap::new(width, height);
+    for (i, &on) in pixels.iter().take(width * height).enumerate() {
+        image.set(i % width, i / width, on);
+    }
+    Ok(image)
+}
+
+/// Read a PNG file, dark pixels being lit
+///
+/// # Errors
+///
+/// Returns an [`ImageFormatError`] if the file is incorrect.
+#[cfg(feature = "png")]
+pub fn from_png(data: &[u8]) -> Result<Bitmap, ImageFormatError> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()
+        .map_err(|e| ImageFormatError::Png(e.to_string()))?;
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+    let info = reader.next_frame(&mut buffer)
+        .map_err(|e| ImageFormatError::Png(e.to_string()))?;
+    let channels = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+    let mut image = Bitmap::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = &buffer[y * info.line_size + x * channels..][..channels];
+            // Only look at the colour, not the alpha channel
+            let colour = &pixel[..channels.min(3)];
+            let grey = colour.iter().map(|&c| c as usize).sum::<usize>() / colour.len();
+            image.set(x, y, grey < 128);
+        }
+    }
+    Ok(image)
+}
+
+/// Read an image file, guessing its format from its contents
+///
+/// # Errors
+///
+/// Returns an [`ImageFormatError`] if the file is incorrect.
+pub fn read_image(data: &[u8]) -> Result<Bitmap, ImageFormatError> {
+    #[cfg(feature = "png")]
+    if data.starts_with(b"\x89PNG") {
+        return from_png(data);
+    }
+    from_netpbm(data)
+}
+
+/// Enhance an image, writing a file for every frame
+///
+/// Frame 0 is the image before enhancement. All frames have the size of
+/// the last one, with the background filling the space around the image.
+///
+/// # Arguments
+///
+///  - `enhancer` : the [`DenseEnhancer`] to run.
+///  - `steps` : the number of enhancement steps.
+///  - `dir` : the directory where frames are written, as `frame_0000.pbm`
+///    and so on.
+///  - `format` : the [`ImageFormat`] of the frames.
+///
+/// # Return Value
+///
+/// Returns the paths of all of the frames, in order.
+///
+/// # Errors
+///
+/// Returns the first error met while writing files.
+pub fn animate(enhancer: &mut DenseEnhancer, steps: usize, dir: &Path,
+    format: ImageFormat) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::with_capacity(steps + 1);
+    for frame in 0..=steps {
+        if frame > 0 { enhancer.step(); }
+        let path = dir.join(format!("frame_{:04}.{}", frame, format.extension()));
+        std::fs::write(&path, format.encode(&enhancer.framed(steps - frame)))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
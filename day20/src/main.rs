@@ -2,12 +2,59 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day20;
+use day20::{solve_part_one, solve_part_two, Automaton, DenseEnhancer, Neighbourhood, Rule};
+use day20::imageio::{animate, read_image, ImageFormat};
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    let mut enhancer = data.parse::<DenseEnhancer>()?;
+    // `--image <file>` starts from a PBM, PGM or PNG image instead
+    let image = std::env::args().skip_while(|a| a != "--image").nth(1);
+    if let Some(file) = &image {
+        let image = read_image(&std::fs::read(file)?)?;
+        enhancer = DenseEnhancer::new(enhancer.program().clone(), image)?;
+    }
+    // `--life <rule> [steps]` runs a Life-like rule such as `B3/S23` on the
+    // image instead of the program
+    let mut args = std::env::args().skip_while(|a| a != "--life").skip(1);
+    if let Some(rule) = args.next() {
+        let steps = args.next().map_or(Ok(50), |s| s.parse::<usize>())?;
+        let mut life = Automaton::new(enhancer.image().clone(), Neighbourhood::moore(1),
+            rule.parse::<Rule>()?)?;
+        let counts = life.run(steps);
+        print!("{}", life);
+        println!("{:?}", counts.last().copied().flatten());
+        return Ok(());
+    }
+    // `--animate <dir> <format> [steps]` writes one frame per step
+    let mut args = std::env::args().skip_while(|a| a != "--animate").skip(1);
+    if let Some(dir) = args.next() {
+        let format = args.next()
+            .ok_or("expected --animate <dir> <pbm|pgm|png> [steps]")?
+            .parse::<ImageFormat>()?;
+        let steps = args.next().map_or(Ok(50), |s| s.parse::<usize>())?;
+        let frames = animate(&mut enhancer, steps, std::path::Path::new(&dir), format)?;
+        println!("{} frames written to {}", frames.len(), dir);
+        return Ok(());
+    }
+    // `--export <pbm|pgm|png> <file>` writes the image after 50 steps
+    let mut args = std::env::args().skip_while(|a| a != "--export").skip(1);
+    if let Some(format) = args.next() {
+        let format = format.parse::<ImageFormat>()?;
+        let file = args.next().ok_or("expected --export <pbm|pgm|png> <file>")?;
+        enhancer.run(50);
+        std::fs::write(file, format.encode(&enhancer.framed(1)))?;
+        return Ok(());
+    }
+    // Without any other mode, enhance the image as in both parts
+    if image.is_some() {
+        let counts = enhancer.run(50);
+        println!("{:?}", counts[1]);
+        println!("{:?}", counts[49]);
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -18,7 +65,111 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod test {
     use super::*;
     use common::test;
+    use day20::{Bitmap, Enhancer, RuleError};
+    use day20::imageio::{from_netpbm, to_pbm, to_pgm, ImageFormatError};
 
     test!(day20_01_example1, 1, 35, "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\n#..#.\n#....\n##..#\n..#..\n..###");
     test!(day20_02_example1, 2, 3351, "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\n#..#.\n#....\n##..#\n..#..\n..###");
+
+    #[test]
+    fn day20_dense_enhancer() {
+        let data = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\n#..#.\n#....\n##..#\n..#..\n..###";
+        let mut dense = data.parse::<DenseEnhancer>().unwrap();
+        let mut sparse = Enhancer::unparse(data);
+        let counts = dense.run(50);
+        assert_eq!((Some(35), Some(3351)), (counts[1], counts[49]));
+        for count in counts.iter().take(10) {
+            sparse.step();
+            assert_eq!(Some(sparse.count()), *count);
+        }
+        // A program lighting up the background makes it flicker
+        let flicker = format!("#{}.\n\n#", ".".repeat(510));
+        let counts = flicker.parse::<DenseEnhancer>().unwrap().run(4);
+        assert_eq!(vec![None, Some(1), None, Some(1)], counts);
+        assert!("#.#\n\n#".parse::<DenseEnhancer>().is_err());
+    }
+
+    #[test]
+    fn day20_image_io() {
+        let data = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\n#..#.\n#....\n##..#\n..#..\n..###";
+        let mut enhancer = data.parse::<DenseEnhancer>().unwrap();
+        enhancer.run(2);
+        let image = enhancer.image().clone();
+        assert_eq!(Ok(image.clone()), from_netpbm(&to_pbm(&image)));
+        assert_eq!(Ok(image.clone()), from_netpbm(&to_pgm(&image)));
+        #[cfg(feature = "png")]
+        assert_eq!(Ok(image.clone()), read_image(&day20::imageio::to_png(&image)));
+        let mut plain = Bitmap::new(3, 2);
+        plain.set(0, 0, true);
+        plain.set(2, 1, true);
+        assert_eq!(Ok(plain.clone()), from_netpbm(b"P1\n# comment\n3 2\n1 0 0\n0 0 1\n"));
+        assert_eq!(Ok(plain), from_netpbm(b"P2 3 2 15\n0 15 15\n15 9 3\n"));
+        assert_eq!(Err(ImageFormatError::Truncated), from_netpbm(b"P1 3 2 1 0 0"));
+        assert_eq!(Err(ImageFormatError::UnknownFormat), from_netpbm(b"P7 3 2"));
+        // Every frame has the size of the last one
+        let dir = std::env::temp_dir().join(format!("day20_frames_{}", std::process::id()));
+        let mut enhancer = data.parse::<DenseEnhancer>().unwrap();
+        let frames = animate(&mut enhancer, 3, &dir, ImageFormat::Pbm).unwrap();
+        assert_eq!(4, frames.len());
+        for frame in &frames {
+            let image = from_netpbm(&std::fs::read(frame).unwrap()).unwrap();
+            assert_eq!((11, 11), (image.width(), image.height()));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Build a bitmap from `#` and `.` rows
+    fn bitmap(rows: &[&str]) -> Bitmap {
+        let mut image = Bitmap::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                image.set(x, y, c == '#');
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn day20_automaton() {
+        // A blinker flips between horizontal and vertical
+        let mut blinker = Automaton::new(bitmap(&["###"]), Neighbourhood::moore(1),
+            Rule::life()).unwrap();
+        assert_eq!(vec![Some(3); 4], blinker.run(4));
+        let frame = blinker.framed(0);
+        assert_eq!((11, 9), (frame.width(), frame.height()));
+        assert!((4..=6).all(|x| frame.get(x, 4, false)));
+        blinker.step();
+        assert!((4..=6).all(|y| blinker.image().get(6, y, false)));
+        // A glider moves on without changing
+        let mut glider = Automaton::new(bitmap(&[".#.", "..#", "###"]),
+            Neighbourhood::moore(1), "b3/s23".parse::<Rule>().unwrap()).unwrap();
+        assert_eq!(vec![Some(5); 8], glider.run(8));
+        // Births with no neighbours light up the background
+        let mut flicker = Automaton::new(Bitmap::new(1, 1), Neighbourhood::moore(1),
+            "B0/S".parse::<Rule>().unwrap()).unwrap();
+        assert_eq!(vec![None, Some(0), None], flicker.run(3));
+        // The program of the puzzle, read from the bottom right
+        let data = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#\n\n#..#.\n#....\n##..#\n..#..\n..###";
+        let mut dense = data.parse::<DenseEnhancer>().unwrap();
+        let reversed = (0..512)
+            .map(|i: usize| dense.program()[i.reverse_bits() >> (usize::BITS - 9)])
+            .collect::<Vec<bool>>();
+        let offsets = Neighbourhood::moore(1).offsets().iter().rev().copied().collect::<Vec<_>>();
+        let mut generic = Automaton::new(dense.image().clone(), Neighbourhood::custom(offsets),
+            Rule::Lookup(reversed)).unwrap();
+        assert_eq!(dense.run(10), generic.run(10));
+        assert_eq!(dense.image(), generic.image());
+        // Neighbourhoods and rules must fit together
+        assert_eq!(5, Neighbourhood::von_neumann(1).offsets().len());
+        assert_eq!((13, 2), (Neighbourhood::von_neumann(2).offsets().len(),
+            Neighbourhood::von_neumann(2).reach()));
+        assert_eq!(Err(RuleError::CountOutOfRange(5)).map(|_: Automaton| ()),
+            Automaton::new(Bitmap::new(1, 1), Neighbourhood::von_neumann(1),
+                "B5/S".parse::<Rule>().unwrap()).map(|_| ()));
+        assert_eq!(Err(RuleError::TableLength { expected: 32, found: 512 }).map(|_: Automaton| ()),
+            Automaton::new(Bitmap::new(1, 1), Neighbourhood::von_neumann(1),
+                Rule::Lookup(dense.program().clone())).map(|_| ()));
+        assert_eq!(Err(RuleError::BadNotation), "B9/S".parse::<Rule>());
+        assert_eq!(Err(RuleError::BadNotation), "3/23".parse::<Rule>());
+    }
 }
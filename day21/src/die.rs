@@ -0,0 +1,172 @@
+//! Module defining the [`Die`] trait and the dice of the game
+
+/// A die with faces numbered from 1
+pub trait Die {
+    /// Number of faces of the die
+    fn sides(&self) -> usize;
+
+    /// Roll the die
+    fn roll(&mut self) -> usize;
+
+    /// Number of times the die was rolled
+    fn rolls(&self) -> usize;
+
+    /// Weight of every face, by increasing face
+    ///
+    /// Weights are how likely every face is relative to the others, or for
+    /// the quantum die, the number of universes every face splits into.
+    /// All faces weigh the same by default.
+    fn distribution(&self) -> Vec<(usize, usize)> {
+        (1..=self.sides()).map(|f| (f, 1)).collect()
+    }
+}
+
+/// A die rolling all of its faces in order, over and over again
+#[derive(Debug, Clone, Copy)]
+pub struct DeterministicDie {
+    sides: usize,
+    cur: usize,
+    used: usize
+}
+
+impl DeterministicDie {
+    /// A die with faces from 1 to `sides`, about to roll 1
+    #[must_use]
+    pub fn new(sides: usize) -> DeterministicDie {
+        DeterministicDie { sides, cur: 1, used: 0 }
+    }
+}
+
+impl Default for DeterministicDie {
+    fn default() -> DeterministicDie {
+        DeterministicDie::new(100)
+    }
+}
+
+impl Die for DeterministicDie {
+    fn sides(&self) -> usize {
+        self.sides
+    }
+
+    fn roll(&mut self) -> usize {
+        let cur = self.cur;
+        self.cur += 1;
+        self.used += 1;
+        if self.cur > self.sides { self.cur = 1; }
+        cur
+    }
+
+    fn rolls(&self) -> usize {
+        self.used
+    }
+}
+
+/// Seeded pseudo-random number generator (`SplitMix64`)
+///
+/// Not suitable for anything but simulations, but fast and reproducible.
+#[derive(Debug, Clone, Copy)]
+struct SplitMix64 {
+    state: u64
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Pick a number in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// A fair die rolling pseudo-random faces, for Monte-Carlo simulations
+///
+/// The same seed always gives the same rolls.
+#[derive(Debug, Clone, Copy)]
+pub struct SeededDie {
+    sides: usize,
+    rng: SplitMix64,
+    used: usize
+}
+
+impl SeededDie {
+    /// A die with faces from 1 to `sides`
+    #[must_use]
+    pub fn new(sides: usize, seed: u64) -> SeededDie {
+        SeededDie { sides, rng: SplitMix64 { state: seed }, used: 0 }
+    }
+}
+
+impl Die for SeededDie {
+    fn sides(&self) -> usize {
+        self.sides
+    }
+
+    fn roll(&mut self) -> usize {
+        self.used += 1;
+        self.rng.below(self.sides) + 1
+    }
+
+    fn rolls(&self) -> usize {
+        self.used
+    }
+}
+
+/// The Dirac die, splitting the universe in one copy per face and roll
+///
+/// Every face can split into several universes, which makes it a loaded
+/// die. Rolling it follows a single universe, picked at random with the
+/// weights of the faces.
+#[derive(Debug, Clone)]
+pub struct QuantumDie {
+    weights: Vec<usize>,
+    rng: SplitMix64,
+    used: usize
+}
+
+impl QuantumDie {
+    /// A die with faces from 1 to `sides`, each one a single universe
+    #[must_use]
+    pub fn new(sides: usize) -> QuantumDie {
+        QuantumDie::weighted(vec![1; sides])
+    }
+
+    /// A die where face `f` splits into `weights[f - 1]` universes
+    #[must_use]
+    pub fn weighted(weights: Vec<usize>) -> QuantumDie {
+        QuantumDie { weights, rng: SplitMix64 { state: 0 }, used: 0 }
+    }
+}
+
+impl Die for QuantumDie {
+    fn sides(&self) -> usize {
+        self.weights.len()
+    }
+
+    fn roll(&mut self) -> usize {
+        self.used += 1;
+        let mut universe = self.rng.below(self.weights.iter().sum());
+        for (f, &w) in self.weights.iter().enumerate() {
+            if universe < w { return f + 1; }
+            universe -= w;
+        }
+        unreachable!()
+    }
+
+    fn rolls(&self) -> usize {
+        self.used
+    }
+
+    fn distribution(&self) -> Vec<(usize, usize)> {
+        self.weights.iter()
+            .enumerate()
+            .filter(|&(_, &w)| w > 0)
+            .map(|(f, &w)| (f + 1, w))
+            .collect()
+    }
+}
@@ -0,0 +1,161 @@
+//! Module defining the rules of a game of Dirac Dice
+
+/// Rules of a game
+///
+/// The board is a loop of `board_size` spaces numbered from 1. Players
+/// take turns rolling a die with `die_sides` faces `rolls_per_turn` times,
+/// move forward by the total, and score the number of the space they land
+/// on. The first to reach `win_score` wins. The number of players is the
+/// number of starting positions the game is played with. Rules are built
+/// with [`GameConfig::new`], which makes sure the game can be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameConfig {
+    /// Number of spaces of the board
+    pub(crate) board_size: usize,
+    /// Number of times the die is rolled every turn
+    pub(crate) rolls_per_turn: usize,
+    /// Number of faces of the die, numbered from 1
+    pub(crate) die_sides: usize,
+    /// Score needed to win
+    pub(crate) win_score: usize
+}
+
+/// Error thrown when building a game that cannot be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameConfigError {
+    /// The board has no spaces
+    EmptyBoard,
+    /// The die has no faces
+    NoDieSides,
+    /// Players never roll the die, so nobody ever wins
+    NoRolls
+}
+impl std::fmt::Display for GameConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameConfigError::EmptyBoard => write!(f, "The board needs at least one space"),
+            GameConfigError::NoDieSides => write!(f, "The die needs at least one face"),
+            GameConfigError::NoRolls => write!(f, "Players need to roll at least once per turn")
+        }
+    }
+}
+impl std::error::Error for GameConfigError { }
+
+impl GameConfig {
+    /// Build the rules of a game
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GameConfigError`] if the board or the die is empty, or if
+    /// players never roll the die.
+    pub fn new(board_size: usize, rolls_per_turn: usize, die_sides: usize, win_score: usize)
+        -> Result<GameConfig, GameConfigError> {
+        if board_size == 0 { return Err(GameConfigError::EmptyBoard); }
+        if die_sides == 0 { return Err(GameConfigError::NoDieSides); }
+        if rolls_per_turn == 0 { return Err(GameConfigError::NoRolls); }
+        Ok(GameConfig { board_size, rolls_per_turn, die_sides, win_score })
+    }
+
+    /// The same game, with another score needed to win
+    #[must_use]
+    pub fn with_win_score(self, win_score: usize) -> GameConfig {
+        GameConfig { win_score, ..self }
+    }
+
+    /// Number of spaces of the board
+    #[must_use]
+    pub fn board_size(&self) -> usize {
+        self.board_size
+    }
+
+    /// Number of times the die is rolled every turn
+    #[must_use]
+    pub fn rolls_per_turn(&self) -> usize {
+        self.rolls_per_turn
+    }
+
+    /// Number of faces of the die, numbered from 1
+    #[must_use]
+    pub fn die_sides(&self) -> usize {
+        self.die_sides
+    }
+
+    /// Score needed to win
+    #[must_use]
+    pub fn win_score(&self) -> usize {
+        self.win_score
+    }
+
+    /// The game of part one, with a 100-sided die and 1000 points to win
+    pub const PRACTICE: GameConfig = GameConfig {
+        board_size: 10,
+        rolls_per_turn: 3,
+        die_sides: 100,
+        win_score: 1000
+    };
+
+    /// The game of part two, with a 3-sided die and 21 points to win
+    pub const DIRAC: GameConfig = GameConfig {
+        board_size: 10,
+        rolls_per_turn: 3,
+        die_sides: 3,
+        win_score: 21
+    };
+
+    /// Move from a space (numbered from 0) by some number of spaces
+    pub(crate) fn advance(&self, position: usize, by: usize) -> usize {
+        (position + by % self.board_size) % self.board_size
+    }
+}
+
+/// A player of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Player {
+    /// Space the player is on, numbered from 0
+    pub position: usize,
+    /// Score of the player
+    pub score: usize
+}
+
+impl Player {
+    /// A player with no points, on a space numbered from 1
+    #[must_use]
+    pub fn new(start: usize, config: &GameConfig) -> Player {
+        Player { position: (start + config.board_size - 1) % config.board_size, score: 0 }
+    }
+
+    /// Move forward and score
+    pub(crate) fn advance(&mut self, by: usize, config: &GameConfig) {
+        self.position = config.advance(self.position, by);
+        self.score += self.position + 1;
+    }
+}
+
+/// Error thrown when parsing an incorrect starting position
+#[derive(Debug)]
+pub struct ParsePlayerError { }
+impl std::fmt::Display for ParsePlayerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error unparsing player!!")
+    }
+}
+impl std::error::Error for ParsePlayerError { }
+
+/// Parse lines like `Player 1 starting position: 4`
+///
+/// # Return Value
+///
+/// Returns the starting spaces of all players, numbered from 1, in order.
+///
+/// # Errors
+///
+/// Returns a [`ParsePlayerError`] on the first incorrect line.
+pub fn parse_starts(data: &str) -> Result<Vec<usize>, ParsePlayerError> {
+    data.trim().split('\n')
+        .map(|x| x.split(" starting position: ")
+            .nth(1)
+            .and_then(|k| k.trim().parse::<usize>().ok())
+            .filter(|&k| k > 0)
+            .ok_or(ParsePlayerError { }))
+        .collect::<Result<Vec<usize>, ParsePlayerError>>()
+}
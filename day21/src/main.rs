@@ -2,12 +2,35 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day21;
+use day21::{solve_part_one, solve_part_two, monte_carlo, parse_starts,
+    win_probabilities, GameConfig, QuantumDie, SeededDie};
+
+/// Print Monte-Carlo estimates next to the exact probabilities of part two
+fn print_estimates(data: &str, games: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let config = GameConfig::DIRAC;
+    let starts = parse_starts(data)?;
+    let exact = win_probabilities(&config, &starts,
+        &QuantumDie::new(config.die_sides()));
+    let estimate = monte_carlo(&config, &starts,
+        &mut SeededDie::new(config.die_sides(), 21), games);
+    for (i, (e, m)) in exact.iter().zip(&estimate).enumerate() {
+        println!("player {}: exact {:.6}, estimated {:.6} over {} games",
+            i + 1, e, m, games);
+    }
+    Ok(())
+}
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--monte-carlo <games>` compares sampled games with exact results
+    let mut args = std::env::args().skip_while(|a| a != "--monte-carlo");
+    if args.next().is_some() {
+        let games = args.next().ok_or("missing value for --monte-carlo")?
+            .parse::<usize>()?;
+        return print_estimates(&data, games);
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -18,7 +41,114 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod test {
     use super::*;
     use common::test;
+    use day21::{play, play_practice, quantum_wins, quantum_wins_with, roll_distribution,
+        DeterministicDie, Die, Player};
+    use std::collections::HashMap;
+
+    /// The former recursive solver, memoising every state of the game
+    fn memoised_wins(config: &GameConfig, starts: &[usize]) -> Vec<u128> {
+        fn solve_for(players: &[Player], config: &GameConfig, rolls: &[(usize, usize)],
+            mem: &mut HashMap<Vec<Player>, Vec<u128>>) -> Vec<u128> {
+            if let Some(wins) = mem.get(players) { return wins.clone(); }
+            let n = players.len();
+            let mut wins = vec![0; n];
+            for &(roll, count) in rolls {
+                let mut moved = players[0];
+                moved.position = (moved.position + roll) % config.board_size();
+                moved.score += moved.position + 1;
+                if moved.score >= config.win_score() {
+                    wins[0] += count as u128;
+                    continue;
+                }
+                let mut next = players[1..].to_vec();
+                next.push(moved);
+                for (i, w) in solve_for(&next, config, rolls, mem).into_iter().enumerate() {
+                    wins[(i + 1) % n] += count as u128 * w;
+                }
+            }
+            mem.insert(players.to_vec(), wins.clone());
+            wins
+        }
+        let players = starts.iter()
+            .map(|&s| Player::new(s, config))
+            .collect::<Vec<Player>>();
+        solve_for(&players, config, &roll_distribution(config), &mut HashMap::new())
+    }
 
     test!(day21_01_example1, 1, 739785, "Player 1 starting position: 4\nPlayer 2 starting position: 8");
     test!(day21_02_example1, 2, 444356092776315, "Player 1 starting position: 4\nPlayer 2 starting position: 8");
+
+    #[test]
+    fn day21_game_config() {
+        assert_eq!(vec![444356092776315, 341960390180808],
+            quantum_wins(&GameConfig::DIRAC, &[4, 8]).unwrap());
+        assert_eq!(vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)],
+            roll_distribution(&GameConfig::DIRAC));
+        let outcome = play_practice(&GameConfig::PRACTICE, &[4, 8]).unwrap();
+        assert_eq!((0, vec![1000, 745], 993), (outcome.winner, outcome.scores, outcome.rolls));
+        // Three players, a single roll of a six-sided die on a board of 7
+        let config = GameConfig::new(7, 1, 6, 10).unwrap();
+        let outcome = play_practice(&config, &[1, 2, 3]).unwrap();
+        assert_eq!((2, vec![8, 6, 11], 6), (outcome.winner, outcome.scores, outcome.rolls));
+        let wins = quantum_wins(&config, &[1, 2, 3]).unwrap();
+        assert_eq!(3, wins.len());
+        assert!(wins[0] > wins[1] && wins[1] > wins[2]);
+        let dirac = GameConfig::DIRAC.with_win_score(1);
+        assert_eq!(vec![27, 0], quantum_wins(&dirac, &[4, 8]).unwrap());
+        // Games that cannot be played
+        use day21::GameConfigError;
+        assert_eq!(Err(GameConfigError::EmptyBoard), GameConfig::new(0, 3, 3, 21));
+        assert_eq!(Err(GameConfigError::NoDieSides), GameConfig::new(10, 3, 0, 21));
+        assert_eq!(Err(GameConfigError::NoRolls), GameConfig::new(10, 0, 3, 21));
+    }
+
+    #[test]
+    fn day21_dice() {
+        let mut die = DeterministicDie::new(3);
+        assert_eq!(vec![1, 2, 3, 1], (0..4).map(|_| die.roll()).collect::<Vec<usize>>());
+        assert_eq!(4, die.rolls());
+        let mut a = SeededDie::new(6, 7);
+        let mut b = SeededDie::new(6, 7);
+        let rolls = (0..1000).map(|_| a.roll()).collect::<Vec<usize>>();
+        assert_eq!(rolls, (0..1000).map(|_| b.roll()).collect::<Vec<usize>>());
+        assert!(rolls.iter().all(|&r| (1..=6).contains(&r)));
+        // A loaded quantum die splits into more universes
+        let loaded = QuantumDie::weighted(vec![2, 1, 1]);
+        assert_eq!(vec![(1, 2), (2, 1), (3, 1)], loaded.distribution());
+        let fair = quantum_wins_with(&GameConfig::DIRAC, &[4, 8], &QuantumDie::new(3));
+        assert_eq!(quantum_wins(&GameConfig::DIRAC, &[4, 8]), fair);
+        // Any die can play a whole game
+        let config = GameConfig::DIRAC;
+        let outcome = play(&config, &[4, 8], &mut SeededDie::new(3, 1)).unwrap();
+        assert!(outcome.scores[outcome.winner] >= 21);
+        // Monte-Carlo estimates get close to the exact probabilities
+        let exact = win_probabilities(&config, &[4, 8], &QuantumDie::new(3));
+        assert!((exact.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let estimate = monte_carlo(&config, &[4, 8], &mut SeededDie::new(3, 42), 20_000);
+        for (e, m) in exact.iter().zip(&estimate) {
+            assert!((e - m).abs() < 0.02, "{} too far from {}", m, e);
+        }
+        assert_eq!(vec![(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)],
+            roll_distribution(&config));
+    }
+
+    #[test]
+    fn day21_dense_solver() {
+        let configs = [
+            (GameConfig::DIRAC, vec![4, 8]),
+            (GameConfig::DIRAC, vec![1, 1]),
+            (GameConfig::DIRAC.with_win_score(15), vec![3, 7, 10]),
+            (GameConfig::new(7, 2, 4, 18).unwrap(), vec![2, 5]),
+            (GameConfig::DIRAC.with_win_score(1), vec![4, 8])
+        ];
+        for (config, starts) in configs {
+            assert_eq!(memoised_wins(&config, &starts), quantum_wins(&config, &starts).unwrap());
+        }
+        // Larger targets need more than 64 bits
+        let wins = quantum_wins(&GameConfig::DIRAC.with_win_score(40), &[4, 8]).unwrap();
+        assert!(wins[0] > u64::MAX as u128);
+        // And much larger ones do not fit at all
+        assert_eq!(Err(day21::UniverseOverflow { }),
+            quantum_wins(&GameConfig::DIRAC.with_win_score(100), &[4, 8]));
+    }
 }
@@ -0,0 +1,93 @@
+//! Module playing single games with a [`Die`]
+
+use crate::die::{DeterministicDie, Die};
+use crate::game::{GameConfig, Player};
+
+/// End of a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// Index of the player who won
+    pub winner: usize,
+    /// Final scores of all players
+    pub scores: Vec<usize>,
+    /// Number of times the die was rolled
+    pub rolls: usize
+}
+
+/// Play a game with any [`Die`]
+///
+/// # Arguments
+///
+///  - `config` : the [`GameConfig`] of the game. The faces of the die
+///    are the ones of `die`, whatever `config.die_sides` is.
+///  - `starts` : the starting space of every player, numbered from 1.
+///  - `die` : the [`Die`] to roll.
+///
+/// # Return Value
+///
+/// Returns the [`Outcome`] of the game, or `None` without players. The
+/// number of rolls only counts the ones of this game.
+pub fn play<D: Die>(config: &GameConfig, starts: &[usize], die: &mut D)
+    -> Option<Outcome> {
+    let mut players = starts.iter()
+        .map(|&s| Player::new(s, config))
+        .collect::<Vec<Player>>();
+    if players.is_empty() { return None; }
+    let first_roll = die.rolls();
+    for turn in (0..players.len()).cycle() {
+        let roll = (0..config.rolls_per_turn)
+            .map(|_| die.roll())
+            .sum::<usize>();
+        players[turn].advance(roll, config);
+        if players[turn].score >= config.win_score {
+            return Some(Outcome {
+                winner: turn,
+                scores: players.iter().map(|p| p.score).collect(),
+                rolls: die.rolls() - first_roll
+            });
+        }
+    }
+    unreachable!()
+}
+
+/// Play a game with a [`DeterministicDie`]
+///
+/// # Arguments
+///
+///  - `config` : the [`GameConfig`] of the game.
+///  - `starts` : the starting space of every player, numbered from 1.
+///
+/// # Return Value
+///
+/// Returns the [`Outcome`] of the game, or `None` without players.
+#[must_use]
+pub fn play_practice(config: &GameConfig, starts: &[usize]) -> Option<Outcome> {
+    play(config, starts, &mut DeterministicDie::new(config.die_sides))
+}
+
+/// Estimate the probability of every player winning
+///
+/// # Arguments
+///
+///  - `config` : the [`GameConfig`] of the games.
+///  - `starts` : the starting space of every player, numbered from 1.
+///  - `die` : the [`Die`] to roll, usually a [`SeededDie`].
+///  - `games` : the number of games to play.
+///
+/// # Return Value
+///
+/// Returns the share of the games won by every player, in order.
+///
+/// [`SeededDie`]: crate::die::SeededDie
+pub fn monte_carlo<D: Die>(config: &GameConfig, starts: &[usize], die: &mut D,
+    games: usize) -> Vec<f64> {
+    let mut wins = vec![0; starts.len()];
+    for _ in 0..games {
+        if let Some(outcome) = play(config, starts, die) {
+            wins[outcome.winner] += 1;
+        }
+    }
+    wins.into_iter()
+        .map(|w| w as f64 / games.max(1) as f64)
+        .collect()
+}
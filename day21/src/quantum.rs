@@ -0,0 +1,214 @@
+//! Module playing games with the Dirac die
+//!
+//! Every roll of the Dirac die splits the universe in one copy per face.
+//! Rather than following every universe, the sums of a whole turn are
+//! counted once.
+//!
+//! Players never interact, so the universes of every player are counted
+//! on their own, turn after turn, in a dense array of `(position, score)`
+//! states. A player then wins on their `t`-th turn in all of the
+//! universes where they reach the winning score on that turn, while the
+//! players before them have not won after `t` turns, and the players after
+//! them have not won after `t - 1` turns.
+
+use crate::die::{Die, QuantumDie};
+use crate::game::{GameConfig, Player};
+
+/// Distribution of the sum of the rolls of a turn
+///
+/// # Arguments
+///
+///  - `die` : the [`Die`] rolled, with its [`Die::distribution`].
+///  - `rolls_per_turn` : the number of times it is rolled every turn.
+///
+/// # Return Value
+///
+/// Returns every possible sum, with its weight, by increasing sum. For
+/// the [`QuantumDie`], that weight is the number of universes it happens in.
+#[must_use]
+pub fn turn_distribution<D: Die>(die: &D, rolls_per_turn: usize) -> Vec<(usize, usize)> {
+    let faces = die.distribution();
+    let mut counts = vec![1];
+    for _ in 0..rolls_per_turn {
+        let mut next = vec![0; counts.len() + die.sides()];
+        for (sum, &n) in counts.iter().enumerate() {
+            for &(face, weight) in &faces {
+                next[sum + face] += n * weight;
+            }
+        }
+        counts = next;
+    }
+    counts.into_iter()
+        .enumerate()
+        .filter(|&(_, n)| n > 0)
+        .collect::<Vec<(usize, usize)>>()
+}
+
+/// Distribution of the sum of the rolls of a turn of the Dirac die
+#[must_use]
+pub fn roll_distribution(config: &GameConfig) -> Vec<(usize, usize)> {
+    turn_distribution(&QuantumDie::new(config.die_sides), config.rolls_per_turn)
+}
+
+/// Error thrown when the number of universes does not fit a `u128`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UniverseOverflow { }
+impl std::fmt::Display for UniverseOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Too many universes to count in a u128")
+    }
+}
+impl std::error::Error for UniverseOverflow { }
+
+/// Count the universes in which every player wins
+///
+/// # Arguments
+///
+///  - `config` : the [`GameConfig`] of the game.
+///  - `starts` : the starting space of every player, numbered from 1.
+///
+/// # Return Value
+///
+/// Returns the number of winning universes of every player, in order.
+/// With the board and die of the puzzle, those counts fit a `u128` for
+/// winning scores up to about 55.
+///
+/// # Errors
+///
+/// Returns [`UniverseOverflow`] if a count does not fit a `u128`.
+pub fn quantum_wins(config: &GameConfig, starts: &[usize])
+    -> Result<Vec<u128>, UniverseOverflow> {
+    quantum_wins_with(config, starts, &QuantumDie::new(config.die_sides))
+}
+
+/// Count the universes in which every player wins with any [`Die`]
+///
+/// Every face of the die splits the universe as many times as its weight.
+///
+/// # Errors
+///
+/// Returns [`UniverseOverflow`] if a count does not fit a `u128`.
+pub fn quantum_wins_with<D: Die>(config: &GameConfig, starts: &[usize], die: &D)
+    -> Result<Vec<u128>, UniverseOverflow> {
+    let rolls = turn_distribution(die, config.rolls_per_turn).into_iter()
+        .map(|(sum, n)| (sum, n as u128))
+        .collect::<Vec<(usize, u128)>>();
+    solve_players(config, starts, &rolls).ok_or(UniverseOverflow { })
+}
+
+/// Compute the exact probability of every player winning with a [`Die`]
+///
+/// Contrary to the share of universes won, longer games are not more
+/// likely here. These are the probabilities [`monte_carlo`] estimates.
+///
+/// [`monte_carlo`]: crate::practice::monte_carlo
+#[must_use]
+pub fn win_probabilities<D: Die>(config: &GameConfig, starts: &[usize], die: &D)
+    -> Vec<f64> {
+    let rolls = turn_distribution(die, config.rolls_per_turn);
+    let total = rolls.iter().map(|&(_, n)| n).sum::<usize>() as f64;
+    let rolls = rolls.into_iter()
+        .map(|(sum, n)| (sum, n as f64 / total))
+        .collect::<Vec<(usize, f64)>>();
+    solve_players(config, starts, &rolls).expect("probabilities never overflow")
+}
+
+/// Weight of some games, either a number of universes or a probability
+trait Weight: Copy + Default + PartialEq + From<u8> {
+    /// Sum of two weights, or `None` if it overflows
+    fn plus(self, other: Self) -> Option<Self>;
+    /// Product of two weights, or `None` if it overflows
+    fn times(self, other: Self) -> Option<Self>;
+}
+
+impl Weight for u128 {
+    fn plus(self, other: Self) -> Option<Self> {
+        self.checked_add(other)
+    }
+
+    fn times(self, other: Self) -> Option<Self> {
+        self.checked_mul(other)
+    }
+}
+
+impl Weight for f64 {
+    fn plus(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn times(self, other: Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+/// Weight of the games of a single player, turn by turn
+struct Timeline<T> {
+    /// Weight of the games still going on after every turn, from turn 0
+    alive: Vec<T>,
+    /// Weight of the games won on every turn, from turn 0
+    won: Vec<T>
+}
+
+impl<T: Weight> Timeline<T> {
+    /// Play all of the games of a player until they have all been won, or
+    /// until their weight overflows
+    fn new(player: Player, config: &GameConfig, rolls: &[(usize, T)]) -> Option<Timeline<T>> {
+        let scores = config.win_score.max(1);
+        // Weight of every state, indexed by `position * scores + score`
+        let mut states = vec![T::default(); config.board_size * scores];
+        states[player.position * scores] = T::from(1);
+        let mut alive = vec![T::from(1)];
+        let mut won = vec![T::default()];
+        while *alive.last().unwrap() != T::default() {
+            let mut next = vec![T::default(); states.len()];
+            let mut turn_won = T::default();
+            let mut turn_alive = T::default();
+            for (state, &weight) in states.iter().enumerate() {
+                if weight == T::default() { continue; }
+                for &(roll, count) in rolls {
+                    let mut p = Player { position: state / scores, score: state % scores };
+                    p.advance(roll, config);
+                    let games = weight.times(count)?;
+                    if p.score >= config.win_score {
+                        turn_won = turn_won.plus(games)?;
+                    } else {
+                        let next_state = &mut next[p.position * scores + p.score];
+                        *next_state = next_state.plus(games)?;
+                        turn_alive = turn_alive.plus(games)?;
+                    }
+                }
+            }
+            states = next;
+            alive.push(turn_alive);
+            won.push(turn_won);
+        }
+        Some(Timeline { alive, won })
+    }
+
+    /// Weight of the games still going on after a turn
+    fn alive(&self, turn: usize) -> T {
+        self.alive.get(turn).copied().unwrap_or_default()
+    }
+}
+
+fn solve_players<T: Weight>(config: &GameConfig, starts: &[usize], rolls: &[(usize, T)])
+    -> Option<Vec<T>> {
+    let timelines = starts.iter()
+        .map(|&s| Timeline::new(Player::new(s, config), config, rolls))
+        .collect::<Option<Vec<Timeline<T>>>>()?;
+    timelines.iter()
+        .enumerate()
+        .map(|(i, timeline)| {
+            let mut wins = T::default();
+            for (turn, &won) in timeline.won.iter().enumerate().skip(1) {
+                let mut weight = won;
+                for (j, other) in timelines.iter().enumerate() {
+                    if j < i { weight = weight.times(other.alive(turn))?; }
+                    if j > i { weight = weight.times(other.alive(turn - 1))?; }
+                }
+                wins = wins.plus(weight)?;
+            }
+            Some(wins)
+        })
+        .collect::<Option<Vec<T>>>()
+}
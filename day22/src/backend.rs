@@ -0,0 +1,81 @@
+//! Module defining the interface shared by all of the reboot backends
+
+use crate::modification::Modification;
+use crate::cuboidunion::CuboidUnion;
+use crate::signedcuboids::SignedCuboids;
+use crate::compressedgrid::CompressedGrid;
+
+/// A way of computing the outcome of a reboot sequence
+///
+/// All backends are given the same list of [Modification] rules, in the
+/// order they appear in the input, and must agree on the number of cubes
+/// that are left on once every rule has been applied.
+pub trait RebootBackend<const N: usize> {
+    /// Count the cubes that are on after applying all of the `rules`
+    fn count_lit(rules: &[Modification<N>]) -> u128;
+}
+
+/// Selector for one of the available [`RebootBackend`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// Disjoint cuboid union built from the last rule backwards
+    /// (see [`CuboidUnion`])
+    Union,
+    /// Inclusion–exclusion over signed cuboids (see [`SignedCuboids`])
+    #[default]
+    Signed,
+    /// Coordinate compression with a bitset (see [`CompressedGrid`])
+    Compressed
+}
+
+impl Backend {
+    /// All of the backends, in the order they are usually listed
+    pub const ALL: [Backend; 3] = [
+        Backend::Union, Backend::Signed, Backend::Compressed
+    ];
+
+    /// Count the cubes that are on after applying all of the `rules` with
+    /// the selected backend
+    #[must_use]
+    pub fn count_lit<const N: usize>(self, rules: &[Modification<N>]) -> u128 {
+        match self {
+            Backend::Union => CuboidUnion::<N>::count_lit(rules),
+            Backend::Signed => SignedCuboids::<N>::count_lit(rules),
+            Backend::Compressed => CompressedGrid::<N>::count_lit(rules)
+        }
+    }
+
+    /// Name of the backend, as accepted by its [`FromStr`] implementation
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Union => "union",
+            Backend::Signed => "signed",
+            Backend::Compressed => "compressed"
+        }
+    }
+}
+
+/// Error thrown when parsing an unknown backend name into a [Backend]
+#[derive(Debug)]
+pub struct ParseBackendError {
+    name: String
+}
+impl std::fmt::Display for ParseBackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown backend {:?} (expected one of union, signed, compressed)",
+            self.name)
+    }
+}
+impl std::error::Error for ParseBackendError { }
+
+impl std::str::FromStr for Backend {
+    type Err = ParseBackendError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        Backend::ALL.into_iter()
+            .find(|b| b.name() == data)
+            .ok_or_else(|| ParseBackendError { name: data.to_string() })
+    }
+}
@@ -0,0 +1,147 @@
+//! Module defining [`CompressedGrid`], a coordinate compression backend
+
+use crate::backend::RebootBackend;
+use crate::modification::{Action, Modification};
+
+/// Representation of the reactor on a compressed grid
+///
+/// Only the coordinates where a rule begins or ends matter, so every axis
+/// is cut at those coordinates. Each cell of the resulting (irregular)
+/// grid is either fully on or fully off, and is stored as a single bit.
+pub struct CompressedGrid<const N: usize = 3> {
+    /// Sorted cut points along each axis. Cell `i` of an axis spans
+    /// `axes[a][i]..axes[a][i+1]` (exclusive upper bound).
+    axes: [Vec<isize>; N],
+    /// Number of `u64` words used by a single row along the last axis
+    row_words: usize,
+    /// The bits of all cells, rows of the last axis laid out in order
+    /// of the other axis
+    bits: Vec<u64>
+}
+
+impl<const N: usize> CompressedGrid<N> {
+    /// Build an empty grid able to hold all of the provided rules
+    #[must_use]
+    pub fn new(rules: &[Modification<N>]) -> CompressedGrid<N> {
+        let mut axes: [Vec<isize>; N] = std::array::from_fn(|_| Vec::new());
+        for r in rules {
+            let (low, high) = r.get_cube();
+            for (a, axis) in axes.iter_mut().enumerate() {
+                axis.extend([low[a], high[a] + 1]);
+            }
+        }
+        for axis in &mut axes {
+            axis.sort_unstable();
+            axis.dedup();
+        }
+        let row_words = axes.last().map_or(0, |a| cells(a).div_ceil(64));
+        let rows = axes[..N.saturating_sub(1)].iter()
+            .map(|a| cells(a))
+            .product::<usize>();
+        CompressedGrid { axes, row_words, bits: vec![0; rows * row_words] }
+    }
+
+    /// Find the range of cell indices covered by `low..=high` on an axis
+    fn cell_range(&self, axis: usize, low: isize, high: isize)
+        -> std::ops::Range<usize> {
+        // Both bounds are guaranteed to be cut points since the grid
+        // was built from the very rules that are applied on it.
+        let start = self.axes[axis].binary_search(&low).unwrap();
+        let end = self.axes[axis].binary_search(&(high + 1)).unwrap();
+        start..end
+    }
+
+    /// Apply a rule to the grid
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule was not part of the ones used to build the grid
+    /// with [`CompressedGrid::new`].
+    pub fn apply(&mut self, rule: &Modification<N>) {
+        if N == 0 { return; }
+        let (low, high) = rule.get_cube();
+        let ranges: [std::ops::Range<usize>; N] =
+            std::array::from_fn(|a| self.cell_range(a, low[a], high[a]));
+        let (zs, leading) = ranges.split_last().unwrap();
+        // Walk through every row of the rule like an odometer
+        let mut index = leading.iter()
+            .map(|r| r.start)
+            .collect::<Vec<usize>>();
+        loop {
+            let row = index.iter().zip(&self.axes)
+                .fold(0, |acc, (&i, axis)| acc * cells(axis) + i)
+                * self.row_words;
+            // Set, clear or flip the bits of the last axis range word by word
+            let mut z = zs.start;
+            while z < zs.end {
+                let word = z / 64;
+                let first = z % 64;
+                let last = (zs.end - word * 64).min(64);
+                let mask = if last - first == 64 { u64::MAX }
+                    else { ((1u64 << (last - first)) - 1) << first };
+                match rule.action() {
+                    Action::On => self.bits[row + word] |= mask,
+                    Action::Off => self.bits[row + word] &= !mask,
+                    Action::Toggle => self.bits[row + word] ^= mask
+                }
+                z = (word + 1) * 64;
+            }
+            // Move on to the next row
+            let mut a = leading.len();
+            loop {
+                if a == 0 { return; }
+                a -= 1;
+                index[a] += 1;
+                if index[a] < leading[a].end { break; }
+                index[a] = leading[a].start;
+            }
+        }
+    }
+
+    /// Count the cubes that are currently lit
+    #[must_use]
+    pub fn volume(&self) -> u128 {
+        if self.row_words == 0 { return 0; }
+        let width = |axis: usize, i: usize|
+            self.axes[axis][i + 1].abs_diff(self.axes[axis][i]) as u128;
+        self.bits.chunks(self.row_words)
+            .enumerate()
+            .map(|(row, words)| {
+                // Unfold the row number into the cell index of every
+                // leading axis to get the area of the row
+                let mut rest = row;
+                let mut area = 1;
+                for a in (0..N - 1).rev() {
+                    let count = cells(&self.axes[a]);
+                    area *= width(a, rest % count);
+                    rest /= count;
+                }
+                let mut depth = 0;
+                for (w, &word) in words.iter().enumerate() {
+                    let mut word = word;
+                    while word != 0 {
+                        let z = w * 64 + word.trailing_zeros() as usize;
+                        depth += width(N - 1, z);
+                        word &= word - 1;
+                    }
+                }
+                area * depth
+            })
+            .sum()
+    }
+}
+
+/// Number of cells along an axis given its cut points
+fn cells(axis: &[isize]) -> usize {
+    axis.len().saturating_sub(1)
+}
+
+impl<const N: usize> RebootBackend<N> for CompressedGrid<N> {
+    fn count_lit(rules: &[Modification<N>]) -> u128 {
+        let mut grid = CompressedGrid::new(rules);
+        for r in rules {
+            grid.apply(r);
+        }
+        grid.volume()
+    }
+}
@@ -1,18 +1,20 @@
 //! Module defining a [`CuboidUnion`] that keeps track of the union of
 //! multiple cuboids.
 
-use crate::utils::{Cuboid, intersection, volume, split_cubes};
+use crate::backend::RebootBackend;
+use crate::modification::{Action, Modification};
+use crate::utils::{Cuboid, intersection, volume, split_cubes, difference};
 
 /// Representation of a union of many cuboids
 ///
 /// Internally, this is just a wrapper for a [Vec] of [Cuboid].
 /// Historically, it wasn't always, but I find the implementation
 /// of the structure fairly useful.
-pub struct CuboidUnion {
-    cuboids: Vec<Cuboid>
+pub struct CuboidUnion<const N: usize = 3> {
+    cuboids: Vec<Cuboid<N>>
 }
 
-impl CuboidUnion {
+impl<const N: usize> CuboidUnion<N> {
     /// Add a cuboid to the set represented by the Union
     ///
     /// # Return Value
@@ -20,6 +22,23 @@ impl CuboidUnion {
     /// Returns the difference in volume that the addition of the
     /// provided [Cuboid] creates.
     ///
+    /// # Panics
+    ///
+    /// See [`CuboidUnion::insert`].
+    #[must_use]
+    pub fn add(&mut self, c: Cuboid<N>) -> u128 {
+        self.insert(c).into_iter()
+            .map(volume)
+            .sum::<u128>()
+    }
+
+    /// Add a cuboid to the set represented by the Union
+    ///
+    /// # Return Value
+    ///
+    /// Returns the non-intersecting pieces of the provided [Cuboid] that
+    /// were not already part of the union, and are now.
+    ///
     /// # Mechanism
     ///
     /// In order to add a [Cuboid] to the current covering set,
@@ -57,10 +76,9 @@ impl CuboidUnion {
     ///
     /// It should never happen, but if it does, know that something
     /// is deeply broken.
-    #[must_use]
-    pub fn add(&mut self, c: Cuboid) -> usize {
+    pub fn insert(&mut self, c: Cuboid<N>) -> Vec<Cuboid<N>> {
         // And now remove the intersections
-        let mut cuboids: Vec<Cuboid> = vec![c];
+        let mut cuboids: Vec<Cuboid<N>> = vec![c];
         for &old_c in &self.cuboids {
             let mut new_cuboids = Vec::new();
             for c in cuboids {
@@ -79,7 +97,7 @@ impl CuboidUnion {
                     // Up my results.
                     //assert_eq!(volume(c), ncubes.iter()
                                //.map(|&x| volume(x))
-                               //.sum::<usize>());
+                               //.sum::<u128>());
                     // We remove precisely the part of the
                     // original cuboid `c` that intersects with
                     // existing regions.
@@ -101,32 +119,84 @@ impl CuboidUnion {
             // Was the case then the delta of volume is 0 and we
             // Don't need to keep iterating over the rest of the
             // Known cuboids from the union.
-            if new_cuboids.is_empty() { return 0; }
+            if new_cuboids.is_empty() { return Vec::new(); }
             cuboids = new_cuboids;
         }
-        let nvol = cuboids.iter()
-            .map(|&x| volume(x))
-            .sum::<usize>();
-        // This check was more useful for debugging
-        // Than you may think it was
-        //assert!(nvol <= volume(c));
         // Add the cuboids that never intersected with anything
         // To the list of cuboids from the known union. That way,
         // That list always contains mutually non-intersecting cuboids.
-        self.cuboids.extend(cuboids);
-        nvol // Exclusive volume added by `c` (i.e. delta of volume)
+        self.cuboids.extend(cuboids.iter().copied());
+        cuboids // Exclusive pieces added by `c`
     }
 
     /// Create a new [`CuboidUnion`] that is empty
     #[must_use]
-    pub fn new() -> CuboidUnion {
+    pub fn new() -> CuboidUnion<N> {
         CuboidUnion { cuboids: Vec::new() }
     }
 }
 
-impl Default for CuboidUnion {
+impl<const N: usize> Default for CuboidUnion<N> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+
+/// Total volume of the overlap between two sets of non-intersecting cuboids
+fn overlap<const N: usize>(a: &[Cuboid<N>], b: &[Cuboid<N>]) -> u128 {
+    a.iter()
+        .flat_map(|&x| b.iter().filter_map(move |&y| intersection(x, y)))
+        .map(volume)
+        .sum::<u128>()
+}
+
+/// Symmetric difference between non-intersecting cuboids and a cuboid
+///
+/// The result is still made of non-intersecting cuboids.
+fn toggle<const N: usize>(set: &[Cuboid<N>], c: Cuboid<N>) -> Vec<Cuboid<N>> {
+    let mut added = vec![c];
+    for &old_c in set {
+        added = added.into_iter()
+            .flat_map(|x| difference(x, old_c))
+            .collect::<Vec<Cuboid<N>>>();
+    }
+    set.iter()
+        .flat_map(|&old_c| difference(old_c, c))
+        .chain(added)
+        .collect::<Vec<Cuboid<N>>>()
+}
+
+impl<const N: usize> RebootBackend<N> for CuboidUnion<N> {
+    fn count_lit(rules: &[Modification<N>]) -> u128 {
+        // The rules are considered in reverse order so that once a rule
+        // covers an area, it is considered to be defined by that rule.
+        // Adding a cuboid to the union yields the pieces it adds: if those
+        // pieces exist on a "on" rule, we found cubes that were turned on
+        // by that rule and never turned off afterwards.
+        //
+        // Toggles do not define an area, but flip whatever state the area
+        // ends up being defined with. So we keep track of the cubes that
+        // later rules toggle an odd number of times.
+        let mut known: CuboidUnion<N> = CuboidUnion::new();
+        let mut flipped: Vec<Cuboid<N>> = Vec::new();
+        let mut solution: u128 = 0;
+        for r in rules.iter().rev() {
+            if r.action() == Action::Toggle {
+                flipped = toggle(&flipped, r.get_cube());
+                continue;
+            }
+            let pieces = known.insert(r.get_cube());
+            let flipped_vol = overlap(&pieces, &flipped);
+            solution += if r.is_on() {
+                pieces.into_iter().map(volume).sum::<u128>() - flipped_vol
+            } else {
+                flipped_vol
+            };
+        }
+        // Cubes never covered by an "on" or "off" rule started off, and
+        // are only on if they were toggled an odd number of times.
+        solution + flipped.iter().map(|&c| volume(c)).sum::<u128>()
+            - overlap(&known.cuboids, &flipped)
+    }
+}
@@ -1,9 +1,14 @@
 //! Library module with all the logic
 pub mod modification;
 pub mod cuboidunion;
+pub mod signedcuboids;
+pub mod compressedgrid;
+pub mod backend;
+pub mod reboot;
 pub mod utils;
 use modification::Modification;
-use cuboidunion::CuboidUnion;
+pub use backend::Backend;
+pub use reboot::{RebootSequence, RebootReport};
 
 /// Solve Advent of Code day 22 part one
 ///
@@ -13,32 +18,30 @@ use cuboidunion::CuboidUnion;
 ///
 /// # Return value
 ///
-/// This function returns a `usize`, the result for part one of advent of code
+/// This function returns a `u128`, the result for part one of advent of code
 /// day 22.
 ///
 /// # Panics
 ///
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
-pub fn solve_part_one(data: &str) -> usize {
-    let rules: Vec<Modification> = data.trim().split('\n')
-        .map(|x| x.parse::<Modification>().unwrap())
-        .rev()
-        .collect::<Vec<Modification>>();
-    let mut res = 0;
-    for x in -50..=50 {
-        for y in -50..=50 {
-            for z in -50..=50 {
-                for r in &rules {
-                    if r.is_within((x, y, z)) {
-                        res += if r.is_on() { 1 } else { 0 };
-                        break;
-                    }
-                }
-            }
-        }
-    }
-    res
+pub fn solve_part_one(data: &str) -> u128 {
+    solve_part_one_with(data, Backend::default())
+}
+
+/// Solve Advent of Code day 22 part one with a specific [Backend]
+///
+/// Rules are clipped to the `-50..=50` initialization region before being
+/// handed to the backend.
+///
+/// # Panics
+///
+/// If any conversion assumed to be valid with the input fails, panics.
+#[must_use]
+pub fn solve_part_one_with(data: &str, backend: Backend) -> u128 {
+    data.parse::<RebootSequence>().unwrap()
+        .run(backend, Some(([-50; 3], [50; 3])))
+        .lit
 }
 
 /// Solve Advent of Code day 22 part two
@@ -49,37 +52,39 @@ pub fn solve_part_one(data: &str) -> usize {
 ///
 /// # Return value
 ///
-/// This function returns a `usize`, the result for part
+/// This function returns a `u128`, the result for part
 /// two of advent of code day 22.
 ///
 /// # Panics
 ///
 /// If any conversion assumed to be valid with the input fails, panics.
 #[must_use]
-pub fn solve_part_two(data: &str) -> usize {
-    // Assemble the rules into an array
-    // They have to be in reverse order so that once a rule covers
-    // An area it is considered to be defined by that rule
-    let rules: Vec<Modification> = data.trim().split('\n')
-        .map(|x| x.parse::<Modification>().unwrap())
-        .rev()
-        .collect::<Vec<Modification>>();
-    // This CuboidUnion will account for all of the cubes covered
-    // By any of the previous rules. Adding to it will yield the
-    // Difference in volume that is caused by the addition of a
-    // New cuboid.
-    let mut known: CuboidUnion = CuboidUnion::new();
-    let mut solution: usize = 0;
-    for r in rules {
-        let excl_volume = known.add(r.get_cube());
-        // If the difference of volume exists on a "on" rule it means
-        // We found cubes that were turned on by that rule and never
-        // Touched afterwards.
-        // Therefore, they are cubes that are turned on.
-        if r.is_on() {
-            solution += excl_volume;
-        }
-    }
-    solution
+pub fn solve_part_two(data: &str) -> u128 {
+    solve_part_two_with(data, Backend::default())
 }
 
+/// Solve Advent of Code day 22 part two with a specific [Backend]
+///
+/// # Panics
+///
+/// If any conversion assumed to be valid with the input fails, panics.
+#[must_use]
+pub fn solve_part_two_with(data: &str, backend: Backend) -> u128 {
+    data.parse::<RebootSequence>().unwrap()
+        .run(backend, None)
+        .lit
+}
+
+/// Parse all of the rules from the input, in order
+///
+/// Rules can have any number of axis `N`, like `on x=0..3,y=-1..2` in 2D.
+///
+/// # Panics
+///
+/// If any of the lines is not a valid rule, panics.
+#[must_use]
+pub fn parse_rules<const N: usize>(data: &str) -> Vec<Modification<N>> {
+    data.trim().split('\n')
+        .map(|x| x.parse::<Modification<N>>().unwrap())
+        .collect::<Vec<Modification<N>>>()
+}
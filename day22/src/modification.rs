@@ -3,53 +3,79 @@ use regex::Regex;
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref RE_MOD: Regex = Regex::new(r"^(on|off) x=(-?\d+)..(-?\d+),y=(-?\d+)..(-?\d+),z=(-?\d+)..(-?\d+)$").unwrap();
+    static ref RE_MOD: Regex = Regex::new(r"^(on|off|toggle) (.+)$").unwrap();
+    static ref RE_RANGE: Regex = Regex::new(r"^[a-z]=(-?\d+)\.\.(-?\d+)$").unwrap();
 }
 
-use crate::utils::{Coords, Cuboid};
+use crate::utils::{Coords, Cuboid, intersection};
+
+/// Effect of a [Modification] on the cubes it covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Turn all cubes on
+    On,
+    /// Turn all cubes off
+    Off,
+    /// Turn off the cubes that are on, and on the ones that are off
+    Toggle
+}
 
 /// Modification rule
 ///
-/// A [Modification] rule describes a [Cuboid] area and the boolean
-/// corresponding to its effect (`true` for "on", `false` for "false").
-#[derive(Debug)]
-pub struct Modification {
-    kind: bool,
-    // I could have used a `Cuboid` here but that type didn't
-    // Exist when I wrote this
-    x_range: (isize, isize),
-    y_range: (isize, isize),
-    z_range: (isize, isize)
+/// A [Modification] rule describes a [Cuboid] area and the [Action]
+/// corresponding to its effect.
+/// The number of axis `N` of the area defaults to the 3 of the puzzle.
+#[derive(Debug, Clone, Copy)]
+pub struct Modification<const N: usize = 3> {
+    action: Action,
+    cube: Cuboid<N>
 }
 
-impl Modification {
+impl<const N: usize> Modification<N> {
     /// Is the provided set of coordinates within our [Cuboid] ?
     ///
     /// Remember that the ranges are inclusive.
     #[must_use]
-    pub fn is_within(&self, a: Coords) -> bool {
-        self.x_range.0 <= a.0 && a.0 <= self.x_range.1 &&
-            self.y_range.0 <= a.1 && a.1 <= self.y_range.1 &&
-            self.z_range.0 <= a.2 && a.2 <= self.z_range.1
+    pub fn is_within(&self, a: Coords<N>) -> bool {
+        (0..N).all(|i| self.cube.0[i] <= a[i] && a[i] <= self.cube.1[i])
     }
 
     /// Fetch the kind of operation for this modification
     ///
     /// # Return Value
     ///
-    /// A `true` boolean corresponds to "on", and a `false` to "off".
+    /// A `true` boolean corresponds to "on", and a `false` to either "off"
+    /// or "toggle".
     #[must_use]
     pub fn is_on(&self) -> bool {
-        self.kind
+        self.action == Action::On
+    }
+
+    /// Fetch the [Action] of this modification
+    #[must_use]
+    pub fn action(&self) -> Action {
+        self.action
     }
 
     /// Fetch the [Cuboid] for this rule
     #[must_use]
-    pub fn get_cube(&self) -> Cuboid {
-        (
-            (self.x_range.0, self.y_range.0, self.z_range.0),
-            (self.x_range.1, self.y_range.1, self.z_range.1)
-        )
+    pub fn get_cube(&self) -> Cuboid<N> {
+        self.cube
+    }
+
+    /// Restrict this rule to a given region
+    ///
+    /// # Return Value
+    ///
+    /// Returns `None` if the rule does not touch `region` at all, and
+    /// `Some(rule)` with a [Modification] of the same kind covering only
+    /// the intersection otherwise.
+    #[must_use]
+    pub fn clip(&self, region: Cuboid<N>) -> Option<Modification<N>> {
+        Some(Modification {
+            action: self.action,
+            cube: intersection(self.cube, region)?
+        })
     }
 }
 
@@ -64,31 +90,54 @@ impl std::fmt::Display for ParseModificationError {
 }
 impl std::error::Error for ParseModificationError { }
 
-impl std::str::FromStr for Modification {
+/// Parse a list of `N` comma-separated ranges like `x=-4..5,y=1..3`
+///
+/// Axis names are single letters and are not checked, only their count
+/// matters. That way, 4D rules can use `w` or any other name they want.
+///
+/// # Errors
+///
+/// Returns a [`ParseModificationError`] if a range is incorrect, or if there
+/// are not exactly `N` of them.
+pub fn parse_cuboid<const N: usize>(data: &str)
+    -> Result<Cuboid<N>, ParseModificationError> {
+    let ranges = data.split(',')
+        .map(|range| {
+            let caps = RE_RANGE.captures(range)
+                .ok_or(ParseModificationError { })?;
+            let low = caps[1].parse::<isize>()
+                .map_err(|_| ParseModificationError { })?;
+            // Cuboids are cut just past their upper bound, which must exist
+            let high = caps[2].parse::<isize>().ok()
+                .filter(|&high| high < isize::MAX)
+                .ok_or(ParseModificationError { })?;
+            Ok((low, high))
+        })
+        .collect::<Result<Vec<(isize, isize)>, ParseModificationError>>()?;
+    if ranges.len() != N {
+        return Err(ParseModificationError { });
+    }
+    Ok((
+        std::array::from_fn(|i| ranges[i].0),
+        std::array::from_fn(|i| ranges[i].1)
+    ))
+}
+
+impl<const N: usize> std::str::FromStr for Modification<N> {
     type Err = ParseModificationError;
     fn from_str(data: &str) -> Result<Self, Self::Err> {
-        let caps = &RE_MOD.captures(data).ok_or(ParseModificationError {  })?;
-        let caps = &caps.iter()
-            // We skip caps[0] because that is the whole string.
-            .skip(1)
-            .map(|x| x.unwrap().as_str())
-            .collect::<Vec<&str>>()[0..7];
-        // Now, caps[0] is the type
-        let kind = caps[0] == "on";
-        // All the rest is numbers for the `Cuboid` of the rule
-        let caps = caps.iter().skip(1)
-            // This line will technically panic instead of
-            // Yielding a `ParseModificationError` but who
-            // Cares, it'll never panic.
-            .map(|&x| x.parse::<isize>().unwrap())
-            .collect::<Vec<isize>>();
+        let caps = RE_MOD.captures(data).ok_or(ParseModificationError {  })?;
+        // Now, caps[1] is the type
+        let action = match &caps[1] {
+            "on" => Action::On,
+            "off" => Action::Off,
+            _ => Action::Toggle
+        };
+        // All the rest is ranges for the `Cuboid` of the rule
         Ok(Modification {
-            kind,
-            x_range: (caps[0], caps[1]),
-            y_range: (caps[2], caps[3]),
-            z_range: (caps[4], caps[5])
+            action,
+            cube: parse_cuboid(&caps[2])?
         })
     }
 
 }
-
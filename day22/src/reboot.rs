@@ -0,0 +1,111 @@
+//! Module defining a full reboot sequence, with its queries
+use regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref RE_QUERY: Regex = Regex::new(r"^query (.+)$").unwrap();
+}
+
+use crate::backend::Backend;
+use crate::modification::{Modification, ParseModificationError, parse_cuboid};
+use crate::utils::{Cuboid, intersection};
+
+/// A single line of a reboot sequence
+#[derive(Debug, Clone, Copy)]
+pub enum Step<const N: usize = 3> {
+    /// An `on`, `off` or `toggle` rule
+    Modify(Modification<N>),
+    /// A `query x=..,y=..,z=..` line, asking how many cubes of the region
+    /// are on at that point of the sequence
+    Query(Cuboid<N>)
+}
+
+impl<const N: usize> std::str::FromStr for Step<N> {
+    type Err = ParseModificationError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        match RE_QUERY.captures(data) {
+            Some(caps) => Ok(Step::Query(parse_cuboid(&caps[1])?)),
+            None => Ok(Step::Modify(data.parse::<Modification<N>>()?))
+        }
+    }
+}
+
+/// Outcome of running a [`RebootSequence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebootReport {
+    /// Number of cubes that are on at the end of the sequence
+    pub lit: u128,
+    /// Answers to all of the queries, in the order they were asked
+    pub queries: Vec<u128>
+}
+
+/// A reboot sequence, as read from an instruction file
+///
+/// Every line is a [Step], either a rule or a query.
+#[derive(Debug, Clone)]
+pub struct RebootSequence<const N: usize = 3> {
+    steps: Vec<Step<N>>
+}
+
+impl<const N: usize> RebootSequence<N> {
+    /// Fetch the steps of the sequence
+    #[must_use]
+    pub fn steps(&self) -> &[Step<N>] {
+        &self.steps
+    }
+
+    /// Run the sequence
+    ///
+    /// # Arguments
+    ///
+    ///  - `backend` : the [Backend] used to count cubes.
+    ///  - `clip` : an optional region outside of which the reactor is
+    ///    ignored entirely. Cubes outside of it are never on, and queries
+    ///    are restricted to it. Part one of the puzzle clips to `-50..=50`.
+    ///
+    /// # Return Value
+    ///
+    /// Returns a [`RebootReport`] with the number of cubes on at the end,
+    /// and the answer to every query.
+    #[must_use]
+    pub fn run(&self, backend: Backend, clip: Option<Cuboid<N>>)
+        -> RebootReport {
+        let clip_rule = |r: &Modification<N>| match clip {
+            Some(region) => r.clip(region),
+            None => Some(*r)
+        };
+        let mut rules: Vec<Modification<N>> = Vec::new();
+        let mut queries: Vec<u128> = Vec::new();
+        for step in &self.steps {
+            match step {
+                Step::Modify(r) => rules.extend(clip_rule(r)),
+                Step::Query(region) => {
+                    // A query is answered by replaying the sequence so far,
+                    // restricted to the queried region.
+                    let region = match clip {
+                        Some(c) => intersection(*region, c),
+                        None => Some(*region)
+                    };
+                    let count = region.map_or(0, |region| {
+                        let local = rules.iter()
+                            .filter_map(|r| r.clip(region))
+                            .collect::<Vec<Modification<N>>>();
+                        backend.count_lit(&local)
+                    });
+                    queries.push(count);
+                }
+            }
+        }
+        RebootReport { lit: backend.count_lit(&rules), queries }
+    }
+}
+
+impl<const N: usize> std::str::FromStr for RebootSequence<N> {
+    type Err = ParseModificationError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let steps = data.trim().split('\n')
+            .map(str::parse::<Step<N>>)
+            .collect::<Result<Vec<Step<N>>, ParseModificationError>>()?;
+        Ok(RebootSequence { steps })
+    }
+}
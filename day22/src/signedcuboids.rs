@@ -0,0 +1,94 @@
+//! Module defining [`SignedCuboids`], an inclusion–exclusion backend
+
+use std::collections::HashMap;
+
+use crate::backend::RebootBackend;
+use crate::modification::{Action, Modification};
+use crate::utils::{Cuboid, intersection, volume};
+
+/// Representation of the lit cubes as a sum of signed cuboids
+///
+/// Every [Cuboid] is weighed by a (possibly negative) multiplicity, and
+/// the number of lit cubes is the weighted sum of their volumes. Rules
+/// are applied in the order they come in, contrary to [`CuboidUnion`].
+///
+/// [`CuboidUnion`]: crate::cuboidunion::CuboidUnion
+pub struct SignedCuboids<const N: usize = 3> {
+    cuboids: HashMap<Cuboid<N>, i128>
+}
+
+impl<const N: usize> SignedCuboids<N> {
+    /// Apply a rule to the current set of lit cubes
+    ///
+    /// # Mechanism
+    ///
+    /// Whatever the kind of the rule, the cubes it covers first have to
+    /// be turned off. For that, every signed cuboid that intersects with
+    /// the rule gets its intersection added with the opposite sign, which
+    /// cancels it out exactly. Then, if the rule turns cubes on, its whole
+    /// [Cuboid] is added with a weight of `1`.
+    ///
+    /// Toggling is the same thing, except that the intersections are
+    /// cancelled out twice: the cubes that were on end up with a weight
+    /// of `-1`, compensated by the whole [Cuboid] being added, and the cubes
+    /// that were off end up with a weight of `1`.
+    ///
+    /// Identical cuboids are merged together, and dropped when their
+    /// weights cancel out, which keeps the collection from blowing up.
+    pub fn apply(&mut self, rule: &Modification<N>) {
+        let cube = rule.get_cube();
+        let factor = if rule.action() == Action::Toggle { 2 } else { 1 };
+        let mut delta: HashMap<Cuboid<N>, i128> = HashMap::new();
+        for (&c, &sign) in &self.cuboids {
+            if let Some(inter) = intersection(c, cube) {
+                *delta.entry(inter).or_insert(0) -= factor * sign;
+            }
+        }
+        if rule.action() != Action::Off {
+            *delta.entry(cube).or_insert(0) += 1;
+        }
+        for (c, sign) in delta {
+            let weight = self.cuboids.entry(c).or_insert(0);
+            *weight += sign;
+            if *weight == 0 {
+                self.cuboids.remove(&c);
+            }
+        }
+    }
+
+    /// Count the cubes that are currently lit
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signed volumes add up to a negative total, which would
+    /// mean the inclusion-exclusion went wrong.
+    #[must_use]
+    pub fn volume(&self) -> u128 {
+        let total = self.cuboids.iter()
+            .map(|(&c, &sign)| sign * volume(c) as i128)
+            .sum::<i128>();
+        u128::try_from(total).expect("signed volumes sum to a negative total")
+    }
+
+    /// Create a new [`SignedCuboids`] with no cube lit
+    #[must_use]
+    pub fn new() -> SignedCuboids<N> {
+        SignedCuboids { cuboids: HashMap::new() }
+    }
+}
+
+impl<const N: usize> Default for SignedCuboids<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> RebootBackend<N> for SignedCuboids<N> {
+    fn count_lit(rules: &[Modification<N>]) -> u128 {
+        let mut lit = SignedCuboids::new();
+        for r in rules {
+            lit.apply(r);
+        }
+        lit.volume()
+    }
+}
@@ -1,10 +1,15 @@
 //! Module for utility definitions
 
 // Some types
-/// Representation of the 3D coordinates of the engine
-pub type Coords = (isize, isize, isize);
-/// Representation of a 3D cuboid within the engine
-pub type Cuboid = (Coords, Coords);
+/// Representation of the coordinates of the engine, along `N` axes
+///
+/// The engine of the puzzle is in 3D, which is the default, but the same
+/// logic applies to 2D rectangles or 4D hyper-cuboids.
+pub type Coords<const N: usize = 3> = [isize; N];
+/// Representation of a cuboid within the engine
+///
+/// It is given as its low-bound and high-bound corners, both inclusive.
+pub type Cuboid<const N: usize = 3> = (Coords<N>, Coords<N>);
 
 /// Determines a possible intersection between cuboids
 ///
@@ -18,35 +23,35 @@ pub type Cuboid = (Coords, Coords);
 /// no intersection between the cuboids, and `Some(intersect)`
 /// when the intersection between both provided cuboids is `intersect`.
 #[must_use]
-pub fn intersection(a: Cuboid, b: Cuboid) -> Option<Cuboid> {
+pub fn intersection<const N: usize>(a: Cuboid<N>, b: Cuboid<N>)
+    -> Option<Cuboid<N>> {
     // It is easy to compute the bounds of the intersection cuboid
     // Its low-bound point is the maximum of the low-bound points,
     // Its high-bound point is the minimum of the high-bound points.
-    let min_of_max = (a.1.0.min(b.1.0), a.1.1.min(b.1.1), a.1.2.min(b.1.2));
-    let max_of_min = (a.0.0.max(b.0.0), a.0.1.max(b.0.1), a.0.2.max(b.0.2));
+    let min_of_max: Coords<N> = std::array::from_fn(|i| a.1[i].min(b.1[i]));
+    let max_of_min: Coords<N> = std::array::from_fn(|i| a.0[i].max(b.0[i]));
     // If either of these coordinates is in the wrong order it means there
     // Is no intersection.
     // Beware! These are strict equalities since our cube boundaries are
     // Inclusive. For example, we would have an intersection between
-    // ((0, 0, 0), (1, 1, 1)) and ((0, 0, 0), (0, 0, 0)), which would be
-    // the former cuboid.
-    if max_of_min.0 > min_of_max.0 ||
-        max_of_min.1 > min_of_max.1 ||
-            max_of_min.2 > min_of_max.2 { None }
-    else {
+    // ([0, 0, 0], [1, 1, 1]) and ([0, 0, 0], [0, 0, 0]), which would be
+    // the latter cuboid.
+    if max_of_min.iter().zip(&min_of_max).any(|(low, high)| low > high) {
+        None
+    } else {
         // If the bounds we computed are valid for a cuboid, it is the
         // Intersection.
         Some((max_of_min, min_of_max))
     }
 }
 
-/// Splits a cuboid into up to 9 cuboids based on an intersecting cuboid
+/// Splits a cuboid into up to `3^N` cuboids based on an intersecting cuboid
 ///
 /// Given a cuboid that intersects with a larger cuboid, this method
 /// returns a [Vec] of the cuboids obtained by splitting the larger one
-/// into up to 9 cuboids (including the intersecting one). This method is
-/// used to split a cuboid into smaller chunks that can be discarded if they
-/// intersect with an existing set of cuboids.
+/// into up to `3^N` cuboids (including the intersecting one). This method
+/// is used to split a cuboid into smaller chunks that can be discarded if
+/// they intersect with an existing set of cuboids.
 ///
 /// # Arguments
 ///
@@ -56,59 +61,78 @@ pub fn intersection(a: Cuboid, b: Cuboid) -> Option<Cuboid> {
 /// # Return value
 ///
 /// Returns a single [Vec] of [Cuboid] with all of the requested cuboids.
-/// The size of that [Vec] can vary from `1` to `9` included.
+/// The size of that [Vec] can vary from `1` to `3^N` included (27 in 3D).
 ///
 /// # Implementation
+///
+/// In order to generate the different cuboids, we begin by splitting
+/// the different axis into three region each :
+/// ```text
+///   full.0                  full.1
+///  ----|-----|----------|------|-->
+///         incube.0   incube.1
+/// ```
+///
+/// Then, for each axis, we take the boundaries of these regions.
+/// The combination of these boundaries for all axis partitions
+/// the space of the cube perfectly into non-intersecting cuboids.
+/// Regions that would be empty are never generated.
 #[must_use]
-pub fn split_cubes(full: Cuboid, incube: Cuboid) -> Vec<Cuboid> {
-    //! In order to generate the different cuboids, we begin by splitting
-    //! the different axis into three region each :
-    //! ```text
-    //!   full.0                  full.1
-    //!  ----|-----|----------|------|-->
-    //!         incube.0   incube.1
-    //! ```
-    //!
-    //! Then, for each axis, we take the boundaries of these regions.
-    //! The combination of these boundaries for all three axis partitions
-    //! the space of the cube perfectly into up to 9 non-intersecting cuboids.
-    let mut cubes = Vec::new();
-    for (lb_x, hb_x) in [
-        (full.0.0, incube.0.0-1),
-        (incube.0.0, incube.1.0),
-        (incube.1.0+1, full.1.0)
-    ] {
-        for (lb_y, hb_y) in [
-            (full.0.1, incube.0.1-1),
-            (incube.0.1, incube.1.1),
-            (incube.1.1+1, full.1.1)
-        ] {
-            for (lb_z, hb_z) in [
-                (full.0.2, incube.0.2-1),
-                (incube.0.2, incube.1.2),
-                (incube.1.2+1, full.1.2)
-            ] {
-                cubes.push(((lb_x, lb_y, lb_z), (hb_x, hb_y, hb_z)));
-            }
+pub fn split_cubes<const N: usize>(full: Cuboid<N>, incube: Cuboid<N>)
+    -> Vec<Cuboid<N>> {
+    let mut cubes = vec![full];
+    for axis in 0..N {
+        let mut regions = Vec::with_capacity(3);
+        // Comparing before subtracting/adding keeps us from overflowing
+        // On cuboids that touch the limits of `isize`
+        if full.0[axis] < incube.0[axis] {
+            regions.push((full.0[axis], incube.0[axis] - 1));
+        }
+        regions.push((incube.0[axis], incube.1[axis]));
+        if incube.1[axis] < full.1[axis] {
+            regions.push((incube.1[axis] + 1, full.1[axis]));
         }
+        cubes = cubes.into_iter()
+            .flat_map(|c| regions.iter().map(move |&(low, high)| {
+                let mut c = c;
+                c.0[axis] = low;
+                c.1[axis] = high;
+                c
+            }))
+            .collect::<Vec<Cuboid<N>>>();
+    }
+    cubes
+}
+
+/// Remove a cuboid from another one
+///
+/// # Return value
+///
+/// Returns the non-intersecting cuboids that cover exactly the cubes of
+/// `full` that are not in `hole`. If both do not intersect, that is just
+/// `full`.
+#[must_use]
+pub fn difference<const N: usize>(full: Cuboid<N>, hole: Cuboid<N>)
+    -> Vec<Cuboid<N>> {
+    match intersection(full, hole) {
+        None => vec![full],
+        Some(incube) => split_cubes(full, incube).into_iter()
+            .filter(|&c| c != incube)
+            .collect::<Vec<Cuboid<N>>>()
     }
-    // Filter out the potential cuboids that are empty/non-existent
-    cubes.into_iter()
-        .filter(|&c| volume(c) > 0)
-        .collect::<Vec<Cuboid>>()
 }
 
 /// Compute the volume of a [Cuboid]
 ///
-/// Note that the volume of a [Cuboid] like `((x, y, z), (x, y, z))`
-/// is `1` (bounds are inclusive).
+/// Note that the volume of a [Cuboid] like `([x, y, z], [x, y, z])`
+/// is `1` (bounds are inclusive). The volume is a `u128` so that cuboids
+/// spanning most of the `isize` range along several axis fit.
 #[must_use]
-pub fn volume(c: Cuboid) -> usize {
-    if c.1.0 < c.0.0 || c.1.1 < c.0.1 || c.1.2 < c.0.2 { 0 }
+pub fn volume<const N: usize>(c: Cuboid<N>) -> u128 {
+    if c.0.iter().zip(&c.1).any(|(low, high)| high < low) { 0 }
     else {
-        (c.1.0 - c.0.0 + 1).unsigned_abs() *
-            (c.1.1 - c.0.1 + 1).unsigned_abs() *
-            (c.1.2 - c.0.2 + 1).unsigned_abs()
+        c.0.iter().zip(&c.1)
+            .map(|(&low, &high)| high.abs_diff(low) as u128 + 1)
+            .product()
     }
 }
-
@@ -2,12 +2,37 @@
 //! all written in Rust.
 extern crate common;
 use common::read_data;
-mod lib;
-use lib::{solve_part_one, solve_part_two};
+extern crate day25;
+use day25::{solve_part_one, solve_part_two, Herd, SeaFloor};
+
+/// Read all of the herds given with `--herd <glyph>,<dy>,<dx>,<priority>`
+/// options
+fn herds_from_args() -> Result<Vec<Herd>, Box<dyn std::error::Error>> {
+    let args = std::env::args().collect::<Vec<String>>();
+    args.windows(2)
+        .filter(|w| w[0] == "--herd")
+        .map(|w| {
+            let fields = w[1].split(',').collect::<Vec<&str>>();
+            let [glyph, dy, dx, priority] = fields[..] else {
+                return Err("expected --herd <glyph>,<dy>,<dx>,<priority>".into());
+            };
+            let glyph = glyph.chars().next().ok_or("missing herd glyph")?;
+            Ok(Herd { glyph, direction: (dy.parse::<isize>()?, dx.parse::<isize>()?),
+                priority: priority.parse::<usize>()? })
+        })
+        .collect()
+}
 
 #[doc(hidden)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data = read_data("input")?;
+    // `--herd <glyph>,<dy>,<dx>,<priority>`, as many times as wanted,
+    // replaces the herds of the puzzle
+    let herds = herds_from_args()?;
+    if !herds.is_empty() {
+        println!("{:?}", SeaFloor::with_herds(&data, &herds)?.simulate());
+        return Ok(());
+    }
     println!("{}", solve_part_one(&data));
     println!("{}", solve_part_two(&data));
     Ok(())
@@ -18,7 +43,64 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod test {
     use super::*;
     use common::test;
+    use day25::{Outcome, SeaFloorError};
 
     test!(day25_01_example1, 1, 58, "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n>.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>");
     test!(day25_02_example1, 2, String::from("Merry Christmas!"), "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n>.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>");
+
+    #[test]
+    fn day25_cycles() {
+        let example = "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n>.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>";
+        let mut floor = example.parse::<SeaFloor>().unwrap();
+        assert_eq!(Outcome::Settled(58), floor.simulate());
+        assert_eq!(example, floor.to_string());
+        assert!(floor.single_step());
+        assert_eq!("....>.>v.>\nv.v>.>v.v.\n>v>>..>v..\n>>v>v>.>.v\n.>v.v...v.\nv>>.>vvv..\n..v...>>..\nvv...>>vv.\n>.v.v..v.v", floor.to_string());
+        // A lone sea cucumber goes round and round
+        assert_eq!(Outcome::Cycle { start: 0, period: 2 },
+            ">.".parse::<SeaFloor>().unwrap().simulate());
+        assert_eq!(Outcome::Cycle { start: 3, period: 5 },
+            ">.\n>.\n..\n.v".parse::<SeaFloor>().unwrap().simulate());
+        assert_eq!(Some(SeaFloorError::RaggedRows), "..>\n.v".parse::<SeaFloor>().err());
+        assert_eq!(Some(SeaFloorError::UnknownGlyph('<')), "..<".parse::<SeaFloor>().err());
+    }
+
+    #[test]
+    fn day25_herds() {
+        // The puzzle, mirrored, with herds moving west and north
+        let example = "v...>>.vv>\n.vv>>.vv..\n>>.>v>...v\n>>v>>.>.v.\nv>v.vv.v..\n>.>>..v...\n.vv..>.>v.\nv.v..>>v.v\n....v..v.>";
+        let mirrored = example.lines()
+            .rev()
+            .map(|l| l.chars().rev().map(|c| match c {
+                '>' => '<',
+                'v' => '^',
+                c => c
+            }).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n");
+        let west = Herd { priority: 0, ..Herd::WEST };
+        let floor = SeaFloor::with_herds(&mirrored, &[Herd::NORTH, west]).unwrap();
+        assert_eq!(Outcome::Settled(58), floor.simulate());
+        // Priorities decide who moves first
+        let mut floor = SeaFloor::with_herds(">.\n.v", &[Herd::EAST, Herd::SOUTH]).unwrap();
+        floor.single_step();
+        assert_eq!(".>\n.v", floor.to_string());
+        let early = Herd { priority: 0, ..Herd::SOUTH };
+        let late = Herd { priority: 1, ..Herd::EAST };
+        let mut floor = SeaFloor::with_herds(">.\n.v", &[late, early]).unwrap();
+        floor.single_step();
+        assert_eq!(">v\n..", floor.to_string());
+        // Diagonal movers, next to a third herd
+        let diagonal = Herd { glyph: '\\', direction: (1, 1), priority: 2 };
+        let mut floor = SeaFloor::with_herds("\\..\n...\n..>",
+            &[diagonal, Herd::EAST, Herd::SOUTH]).unwrap();
+        floor.single_step();
+        assert_eq!("...\n.\\.\n>..", floor.to_string());
+        assert_eq!(Some(SeaFloorError::DuplicateGlyph('v')),
+            SeaFloor::with_herds("v", &[Herd::SOUTH, Herd::SOUTH]).err());
+        assert_eq!(Some(SeaFloorError::DuplicateGlyph('.')),
+            SeaFloor::with_herds("v", &[Herd { glyph: '.', ..Herd::SOUTH }]).err());
+        assert_eq!(Some(SeaFloorError::Empty), "".parse::<SeaFloor>().err());
+        assert_eq!(Some(SeaFloorError::Empty), SeaFloor::with_herds("\n\n", &[Herd::EAST]).err());
+    }
 }
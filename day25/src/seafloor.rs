@@ -0,0 +1,198 @@
+//! Module defining the [`SeaFloor`] and its herds of sea cucumbers
+
+use crate::SeaFloorError;
+
+/// Spot of the sea floor without any sea cucumber
+const EMPTY: char = '.';
+
+/// A herd of sea cucumbers, all facing the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Herd {
+    /// Character standing for a sea cucumber of the herd
+    pub glyph: char,
+    /// Spot the sea cucumbers try to move to, as `(dy, dx)`
+    pub direction: (isize, isize),
+    /// Herds move by increasing priority, ties going to the first herd
+    pub priority: usize
+}
+
+impl Herd {
+    /// The east-facing herd of the puzzle, moving first
+    pub const EAST: Herd = Herd { glyph: '>', direction: (0, 1), priority: 0 };
+    /// The south-facing herd of the puzzle, moving second
+    pub const SOUTH: Herd = Herd { glyph: 'v', direction: (1, 0), priority: 1 };
+    /// A west-facing herd, moving after the herds of the puzzle
+    pub const WEST: Herd = Herd { glyph: '<', direction: (0, -1), priority: 2 };
+    /// A north-facing herd, moving last
+    pub const NORTH: Herd = Herd { glyph: '^', direction: (-1, 0), priority: 3 };
+    /// The herds of the puzzle
+    pub const PUZZLE: [Herd; 2] = [Herd::EAST, Herd::SOUTH];
+}
+
+/// How the herds end up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// No sea cucumber moves anymore on that step, counted from 1
+    Settled(usize),
+    /// The sea cucumbers keep moving: after `start` steps, the sea floor
+    /// comes back to the same state every `period` steps
+    Cycle { start: usize, period: usize }
+}
+
+/// Sea floor, wrapping around on both axes, stored as a dense grid
+///
+/// Every spot holds 0 when empty, or the index of its herd plus one. A
+/// second grid is kept around, so that moving a herd never allocates.
+#[derive(Debug, Clone)]
+pub struct SeaFloor {
+    herds: Vec<Herd>,
+    /// Indices of the herds, in the order they move
+    order: Vec<usize>,
+    cells: Vec<u8>,
+    buffer: Vec<u8>,
+    width: usize,
+    height: usize
+}
+
+impl SeaFloor {
+    /// Read a sea floor with any herds
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SeaFloorError`] if a glyph is not `.` or one of the
+    /// herds, if two herds share a glyph, if there are no spots at all, or
+    /// if rows have different widths.
+    pub fn with_herds(data: &str, herds: &[Herd]) -> Result<SeaFloor, SeaFloorError> {
+        for (i, herd) in herds.iter().enumerate() {
+            if herd.glyph == EMPTY || herds[..i].iter().any(|h| h.glyph == herd.glyph) {
+                return Err(SeaFloorError::DuplicateGlyph(herd.glyph));
+            }
+        }
+        if herds.len() >= u8::MAX as usize {
+            return Err(SeaFloorError::TooManyHerds(herds.len()));
+        }
+        let lines = data.trim().split('\n').collect::<Vec<&str>>();
+        let (width, height) = (lines[0].chars().count(), lines.len());
+        if width == 0 { return Err(SeaFloorError::Empty); }
+        let mut cells = Vec::with_capacity(width * height);
+        for line in lines {
+            if line.chars().count() != width { return Err(SeaFloorError::RaggedRows); }
+            for c in line.chars() {
+                if c == EMPTY {
+                    cells.push(0);
+                    continue;
+                }
+                let herd = herds.iter().position(|h| h.glyph == c)
+                    .ok_or(SeaFloorError::UnknownGlyph(c))?;
+                cells.push(herd as u8 + 1);
+            }
+        }
+        let mut order = (0..herds.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| herds[i].priority);
+        Ok(SeaFloor { herds: herds.to_vec(), order, buffer: cells.clone(), cells,
+            width, height })
+    }
+
+    /// The herds on the sea floor
+    #[must_use]
+    pub fn herds(&self) -> &[Herd] {
+        &self.herds
+    }
+
+    /// Move every sea cucumber of a herd that can move one spot forward
+    fn move_herd(&mut self, herd: usize) -> bool {
+        let id = herd as u8 + 1;
+        let (dy, dx) = self.herds[herd].direction;
+        // Moving backward is moving forward almost all the way around
+        let dy = dy.rem_euclid(self.height as isize) as usize;
+        let dx = dx.rem_euclid(self.width as isize) as usize;
+        let mut moved = false;
+        self.buffer.copy_from_slice(&self.cells);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cells[y * self.width + x] != id { continue; }
+                let next = (y + dy) % self.height * self.width + (x + dx) % self.width;
+                if self.cells[next] == 0 {
+                    self.buffer[y * self.width + x] = 0;
+                    self.buffer[next] = id;
+                    moved = true;
+                }
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.buffer);
+        moved
+    }
+
+    /// Move every herd in turn, by priority
+    ///
+    /// # Return Value
+    ///
+    /// Returns whether any sea cucumber moved.
+    pub fn single_step(&mut self) -> bool {
+        let mut moved = false;
+        for i in 0..self.order.len() {
+            moved |= self.move_herd(self.order[i]);
+        }
+        moved
+    }
+
+    /// Move the herds until they either settle or cycle
+    ///
+    /// Cycles are found with Brent's algorithm, which only keeps a single
+    /// earlier state around.
+    #[must_use]
+    pub fn simulate(&self) -> Outcome {
+        let mut hare = self.clone();
+        let mut tortoise = self.clone();
+        let (mut power, mut period) = (1, 0);
+        let mut step = 0;
+        let period = loop {
+            step += 1;
+            if !hare.single_step() {
+                return Outcome::Settled(step);
+            }
+            period += 1;
+            if hare.cells == tortoise.cells { break period; }
+            if period == power {
+                tortoise.cells.copy_from_slice(&hare.cells);
+                power *= 2;
+                period = 0;
+            }
+        };
+        // Run a second floor `period` steps ahead to find where the cycle starts
+        let mut ahead = self.clone();
+        for _ in 0..period { ahead.single_step(); }
+        let mut behind = self.clone();
+        let mut start = 0;
+        while ahead.cells != behind.cells {
+            ahead.single_step();
+            behind.single_step();
+            start += 1;
+        }
+        Outcome::Cycle { start, period }
+    }
+}
+
+impl std::fmt::Display for SeaFloor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.cells.chunks(self.width).enumerate() {
+            if y > 0 { writeln!(f)?; }
+            for &cell in row {
+                let glyph = match cell {
+                    0 => EMPTY,
+                    id => self.herds[id as usize - 1].glyph
+                };
+                write!(f, "{}", glyph)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a sea floor with the herds of the puzzle
+impl std::str::FromStr for SeaFloor {
+    type Err = SeaFloorError;
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        SeaFloor::with_herds(data, &Herd::PUZZLE)
+    }
+}